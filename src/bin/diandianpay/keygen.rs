@@ -0,0 +1,132 @@
+use crate::{CliResult, Connection};
+use clap::{Args, ValueEnum};
+use diandianpay_rs::framework::keys::{self, DEFAULT_KEY_BITS, KeyFormat};
+use diandianpay_rs::framework::signature::Credentials;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    /// The RSA key size, in bits.
+    #[arg(long, default_value_t = DEFAULT_KEY_BITS)]
+    bits: usize,
+
+    /// The directory the key files are written to.
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// The prefix of the written file names.
+    #[arg(long, default_value = "merchant")]
+    name: String,
+
+    /// The encoding of the PKCS#8 private key file.
+    #[arg(long, value_enum, default_value_t = PrivateFormat::Pem)]
+    private_format: PrivateFormat,
+
+    /// Overwrite existing files.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PrivateFormat {
+    Pem,
+    Der,
+}
+
+/// Generates a merchant keypair.
+///
+/// Writes `<name>_private.{pem,der}` and `<name>_public.pem`, and prints the public key as a single
+/// line of base64 SPKI DER for the DianDianPay portal. When `--merchant-id` and `--dd-public-key`
+/// are given, also writes `<name>_credentials.json`, loadable as `Credentials`.
+pub fn keygen(connection: &Connection, args: KeygenArgs) -> CliResult {
+    eprintln!("generating a {}-bit RSA key...", args.bits);
+    let private_key = keys::generate_private_key(args.bits)?;
+    let public_key = private_key.to_public_key();
+
+    let (format, extension) = match args.private_format {
+        PrivateFormat::Pem => (KeyFormat::Pem, "pem"),
+        PrivateFormat::Der => (KeyFormat::Der, "der"),
+    };
+    let file = |suffix: &str| args.out_dir.join(format!("{}_{suffix}", args.name));
+
+    let private_path = file(&format!("private.{extension}"));
+    write_secret(
+        &private_path,
+        &keys::private_key_to_bytes(&private_key, format)?,
+        args.force,
+    )?;
+    eprintln!("wrote {}", private_path.display());
+
+    let public_path = file("public.pem");
+    write_file(
+        &public_path,
+        &keys::public_key_to_bytes(&public_key, KeyFormat::Pem)?,
+        args.force,
+        false,
+    )?;
+    eprintln!("wrote {}", public_path.display());
+
+    if let (Some(merchant_id), Some(dd_public_key)) =
+        (&connection.merchant_id, &connection.dd_public_key)
+    {
+        let credentials = Credentials::new(
+            merchant_id.clone(),
+            private_key.clone(),
+            keys::read_any_public_key(dd_public_key)?,
+        );
+        let credentials_path = file("credentials.json");
+        write_secret(
+            &credentials_path,
+            serde_json::to_string_pretty(&credentials)?.as_bytes(),
+            args.force,
+        )?;
+        eprintln!("wrote {}", credentials_path.display());
+    }
+
+    eprintln!("public key to upload to DianDianPay:");
+    println!(
+        "{}",
+        String::from_utf8(keys::public_key_to_bytes(
+            &public_key,
+            KeyFormat::Base64Der
+        )?)?
+    );
+
+    Ok(())
+}
+
+/// Writes a file only readable by its owner, on Unix.
+fn write_secret(path: &Path, content: &[u8], force: bool) -> CliResult {
+    write_file(path, content, force, true)
+}
+
+fn write_file(path: &Path, content: &[u8], force: bool, secret: bool) -> CliResult {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    if secret {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("could not create {}: {e}", path.display()))?;
+    // The mode only applies to new files; an overwritten file keeps its own until changed.
+    #[cfg(unix)]
+    if secret {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content)?;
+    Ok(())
+}
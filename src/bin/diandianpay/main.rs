@@ -0,0 +1,216 @@
+//! `diandianpay`, a command-line tool for operating DianDianPay merchant accounts.
+//!
+//! Settings are read from `--settings` (a JSON or TOML file, see [`ClientSettings`]) or from the
+//! `DIANDIANPAY_*` environment variables; the connection flags override either.
+
+mod keygen;
+mod notify;
+mod payment;
+mod signature;
+
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand};
+use diandianpay_rs::framework::Environment;
+use diandianpay_rs::framework::client::blocking_api::HttpApiClient;
+use diandianpay_rs::framework::settings::{
+    ClientSettings, CredentialsSettings, ENV_PREFIX, SettingsError,
+};
+use diandianpay_rs::framework::signature::{Credentials, SignatureAlgorithm};
+use serde::Serialize;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+type CliResult<T = ()> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "diandianpay",
+    version,
+    about = "Operate DianDianPay merchant accounts"
+)]
+struct Cli {
+    #[command(flatten)]
+    connection: Connection,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a payment from a JSON `CreatePaymentRequest`.
+    Create(payment::CreateArgs),
+    /// Query a payment and print its order summary.
+    Query(payment::QueryArgs),
+    /// Print the canonical string and signed headers for a body.
+    Sign(signature::SignArgs),
+    /// Check the signature of captured headers and body, explaining failures.
+    Verify(signature::VerifyArgs),
+    /// Generate a merchant RSA keypair.
+    Keygen(keygen::KeygenArgs),
+    /// Post signed payment notifications to a local handler, to test it without DianDianPay.
+    Notify(notify::NotifyArgs),
+}
+
+/// Where to send requests, and as which merchant.
+#[derive(Args)]
+struct Connection {
+    /// A JSON or TOML settings file. Defaults to the `DIANDIANPAY_*` environment variables.
+    #[arg(long, global = true)]
+    settings: Option<PathBuf>,
+
+    /// `production`, `test` or a base URL.
+    #[arg(long, short = 'e', global = true)]
+    environment: Option<Environment>,
+
+    /// Merchant unique identifier.
+    #[arg(long, global = true)]
+    merchant_id: Option<String>,
+
+    /// The merchant's private key file, RSA or, when built with the `p256` feature, P-256.
+    #[arg(long, global = true)]
+    private_key: Option<PathBuf>,
+
+    /// DianDianPay's public key file, RSA or, when built with the `p256` feature, P-256.
+    #[arg(long, global = true)]
+    dd_public_key: Option<PathBuf>,
+
+    /// The IANA timezone sent with signed requests, e.g. `Asia/Ho_Chi_Minh`.
+    #[arg(long, global = true)]
+    timezone: Option<Tz>,
+
+    /// The merchant's signature algorithm, e.g. `RSA-PSS-SHA256`.
+    #[arg(long, global = true)]
+    algorithm: Option<SignatureAlgorithm>,
+
+    /// The algorithm of DianDianPay's signatures, e.g. `RSA-PSS-SHA256`.
+    #[arg(long, global = true)]
+    dd_algorithm: Option<SignatureAlgorithm>,
+}
+
+impl Connection {
+    /// Loads the settings, applying the flags on top of the file or environment variables.
+    fn settings(&self) -> Result<ClientSettings, SettingsError> {
+        let mut settings = match &self.settings {
+            Some(path) => ClientSettings::from_file(path)?,
+            None => ClientSettings::from_vars(|name| {
+                self.var(name)
+                    .or_else(|| std::env::var(format!("{ENV_PREFIX}{name}")).ok())
+            })?,
+        };
+
+        if let Some(environment) = &self.environment {
+            settings.environment = environment.clone();
+        }
+        if let Some(credentials) = &mut settings.credentials {
+            if let Some(merchant_id) = &self.merchant_id {
+                credentials.merchant_id = merchant_id.clone();
+            }
+            if let Some(path) = &self.private_key {
+                credentials.private_key_path = path.clone();
+            }
+            if let Some(path) = &self.dd_public_key {
+                credentials.dd_public_key_path = path.clone();
+            }
+            if let Some(timezone) = self.timezone {
+                credentials.timezone = Some(timezone);
+            }
+            if let Some(algorithm) = self.algorithm {
+                credentials.algorithm = Some(algorithm);
+            }
+            if let Some(algorithm) = self.dd_algorithm {
+                credentials.dd_algorithm = Some(algorithm);
+            }
+        } else if let (Some(merchant_id), Some(private_key), Some(dd_public_key)) =
+            (&self.merchant_id, &self.private_key, &self.dd_public_key)
+        {
+            settings.credentials = Some(CredentialsSettings {
+                merchant_id: merchant_id.clone(),
+                private_key_path: private_key.clone(),
+                dd_public_key_path: dd_public_key.clone(),
+                timezone: self.timezone,
+                algorithm: self.algorithm,
+                dd_algorithm: self.dd_algorithm,
+                dd_rotation_keys: Vec::new(),
+            });
+        }
+
+        Ok(settings)
+    }
+
+    /// The flag standing in for the environment variable `name`, if set.
+    fn var(&self, name: &str) -> Option<String> {
+        let path = |path: &Option<PathBuf>| path.as_ref().map(|p| p.display().to_string());
+
+        match name {
+            "ENVIRONMENT" => self.environment.as_ref().map(ToString::to_string),
+            "MERCHANT_ID" => self.merchant_id.clone(),
+            "PRIVATE_KEY_PATH" => path(&self.private_key),
+            "DD_PUBLIC_KEY_PATH" => path(&self.dd_public_key),
+            "TIMEZONE" => self.timezone.map(|tz| tz.name().to_string()),
+            "SIGNATURE_ALGORITHM" => self.algorithm.map(|a| a.to_string()),
+            "DD_SIGNATURE_ALGORITHM" => self.dd_algorithm.map(|a| a.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Loads the credentials. No environment is needed when all credential flags are set.
+    fn credentials(&self) -> CliResult<Credentials> {
+        if let (Some(merchant_id), Some(private_key), Some(dd_public_key)) =
+            (&self.merchant_id, &self.private_key, &self.dd_public_key)
+        {
+            let settings = CredentialsSettings {
+                merchant_id: merchant_id.clone(),
+                private_key_path: private_key.clone(),
+                dd_public_key_path: dd_public_key.clone(),
+                timezone: self.timezone,
+                algorithm: self.algorithm,
+                dd_algorithm: self.dd_algorithm,
+                dd_rotation_keys: Vec::new(),
+            };
+            return Ok(settings.load()?);
+        }
+
+        Ok(self.settings()?.load_credentials()?)
+    }
+
+    /// Builds a blocking client, returning it with the merchant id it signs requests as.
+    fn client(&self) -> CliResult<(HttpApiClient, String)> {
+        let settings = self.settings()?;
+        let credentials = settings.load_credentials()?;
+        let merchant_id = credentials.merchant_id.clone();
+        let client =
+            HttpApiClient::new(credentials, settings.client_config(), settings.environment)?;
+
+        Ok((client, merchant_id))
+    }
+}
+
+/// Prints `value` as pretty JSON on stdout.
+fn print_json<T: Serialize>(value: &T) -> CliResult {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn run(cli: Cli) -> CliResult {
+    match cli.command {
+        Command::Create(args) => payment::create(&cli.connection, args),
+        Command::Query(args) => payment::query(&cli.connection, args),
+        Command::Sign(args) => signature::sign(&cli.connection, args),
+        Command::Verify(args) => signature::verify(&cli.connection, args),
+        Command::Keygen(args) => keygen::keygen(&cli.connection, args),
+        Command::Notify(args) => notify::notify(&cli.connection, args),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // The crate's errors already include their source in their message.
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::payment::read_input;
+use crate::{CliResult, Connection};
+use clap::Args;
+use diandianpay_rs::endpoints::payment::notification::PaymentNotification;
+use diandianpay_rs::endpoints::payment::schema::{OrderSummary, PaymentStatus};
+use diandianpay_rs::framework::client::simulator::NotificationSimulator;
+use diandianpay_rs::framework::keys;
+use std::path::PathBuf;
+use url::Url;
+
+#[derive(Args)]
+pub struct NotifyArgs {
+    /// The order as a JSON `OrderSummary`, or `-` to read it from stdin.
+    #[arg(long)]
+    order: PathBuf,
+
+    /// The status to move the order to. Repeat to send one notification per transition, in order,
+    /// e.g. `--status succeeded --status refunded`.
+    #[arg(long = "status", required = true)]
+    statuses: Vec<String>,
+
+    /// The notification handler to post to.
+    #[arg(long)]
+    url: Url,
+
+    /// The private key signing in place of DianDianPay's. The handler must verify with its public
+    /// key. Signs with `--dd-algorithm` and sends `--timezone`, or the configured ones.
+    #[arg(long)]
+    dd_private_key: PathBuf,
+
+    /// Print the notifications instead of sending them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub fn notify(connection: &Connection, args: NotifyArgs) -> CliResult {
+    // The flags win; without a merchant id flag, the configured credentials fill in the rest.
+    let (merchant_id, algorithm, timezone) = match &connection.merchant_id {
+        Some(merchant_id) => (
+            merchant_id.clone(),
+            connection.dd_algorithm,
+            connection.timezone,
+        ),
+        None => {
+            let credentials = connection.credentials()?;
+            (
+                credentials.merchant_id.clone(),
+                Some(credentials.verification_algorithm()),
+                Some(credentials.timezone()),
+            )
+        }
+    };
+
+    let mut simulator = NotificationSimulator::new(
+        merchant_id,
+        keys::read_any_private_key(&args.dd_private_key)?,
+    );
+    if let Some(algorithm) = algorithm {
+        simulator = simulator.with_algorithm(algorithm)?;
+    }
+    if let Some(timezone) = timezone {
+        simulator = simulator.with_timezone(timezone);
+    }
+
+    let mut order: OrderSummary = serde_json::from_str(&read_input(&args.order)?)?;
+    let client = reqwest::blocking::Client::new();
+
+    for name in args.statuses {
+        let status: PaymentStatus = serde_json::from_value(name.clone().into())?;
+        order = NotificationSimulator::apply(&order, status, chrono::Utc::now());
+        let notification = simulator.sign(&PaymentNotification {
+            order: order.clone(),
+        })?;
+
+        if args.dry_run {
+            for (header, value) in &notification.headers {
+                println!("{header}: {}", value.to_str().unwrap_or_default());
+            }
+            println!();
+            println!("{}", notification.body);
+            println!();
+            continue;
+        }
+
+        let response = notification.send_blocking(&client, args.url.clone())?;
+        println!("{name}: {response}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{CliResult, Connection, print_json};
+use clap::Args;
+use diandianpay_rs::endpoints::payment::request::{CreatePaymentRequest, QueryPaymentRequest};
+use diandianpay_rs::framework::client::api::BlockingApiClient;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// The request as JSON, or `-` to read it from stdin. `merchant_id` defaults to the
+    /// configured merchant.
+    file: PathBuf,
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+pub struct QueryArgs {
+    /// Merchant Order Number
+    #[arg(long)]
+    merchant_order_id: Option<String>,
+
+    /// Unique identifier of the DianDian system
+    #[arg(long)]
+    id: Option<String>,
+}
+
+pub fn create(connection: &Connection, args: CreateArgs) -> CliResult {
+    let (client, merchant_id) = connection.client()?;
+
+    let mut request: serde_json::Value = serde_json::from_str(&read_input(&args.file)?)?;
+    if let Some(fields) = request.as_object_mut() {
+        fields
+            .entry("merchant_id")
+            .or_insert_with(|| merchant_id.into());
+    }
+    let request: CreatePaymentRequest = serde_json::from_value(request)?;
+
+    let response = client.create_payment(&request)?;
+    print_json(&response.data.data)
+}
+
+pub fn query(connection: &Connection, args: QueryArgs) -> CliResult {
+    let (client, merchant_id) = connection.client()?;
+
+    let request = QueryPaymentRequest {
+        merchant_id,
+        merchant_order_id: args.merchant_order_id,
+        id: args.id,
+    };
+
+    let response = client.query_payment(&request)?;
+    print_json(&response.data.data.order)
+}
+
+/// Reads `path`, or stdin for `-`.
+pub fn read_input(path: &Path) -> CliResult<String> {
+    if path.as_os_str() == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        return Ok(input);
+    }
+
+    std::fs::read_to_string(path)
+        .map_err(|e| format!("could not read {}: {e}", path.display()).into())
+}
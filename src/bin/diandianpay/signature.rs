@@ -0,0 +1,202 @@
+use crate::payment::read_input;
+use crate::{CliResult, Connection};
+use clap::Args;
+use diandianpay_rs::framework::response::ApiFailure;
+use diandianpay_rs::framework::signature::{Credentials, MatchedKey, VerifySignatureError};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// The exact request body, or `-` to read it from stdin. Trailing newlines are part of the
+    /// body.
+    body: Option<PathBuf>,
+
+    /// The url-encoded query string signed by requests without a body.
+    #[arg(long, conflicts_with = "body")]
+    query: Option<String>,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// The exact body that was received, or `-` to read it from stdin.
+    body: PathBuf,
+
+    /// A captured header, as `name: value`. Repeat for `timestamp`, `timezone` and `signature`.
+    #[arg(long = "header", short = 'H')]
+    headers: Vec<String>,
+
+    /// A file of captured headers, one `name: value` per line.
+    #[arg(long)]
+    headers_file: Option<PathBuf>,
+
+    /// Verify with the merchant's own public key instead of DianDianPay's, to check a request
+    /// signed by this merchant rather than a notification from DianDianPay.
+    #[arg(long)]
+    own_key: bool,
+}
+
+pub fn sign(connection: &Connection, args: SignArgs) -> CliResult {
+    let credentials = connection.credentials()?;
+
+    let content = match (&args.body, args.query) {
+        (Some(path), _) => read_input(path)?,
+        (None, query) => query.unwrap_or_default(),
+    };
+
+    let headers = credentials.headers(&content);
+    let header = |name| {
+        headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_default()
+    };
+
+    println!("canonical string:");
+    println!(
+        "{}",
+        credentials.signing_string(header("timestamp"), header("timezone"), &content)
+    );
+    println!();
+    println!("headers:");
+    for (name, value) in &headers {
+        println!("{name}: {value}");
+    }
+
+    Ok(())
+}
+
+pub fn verify(connection: &Connection, args: VerifyArgs) -> CliResult {
+    let mut credentials = connection.credentials()?;
+    if args.own_key {
+        let own_key = credentials.private_key().to_public_key();
+        let algorithm = credentials.signing_algorithm();
+        credentials = credentials.with_dd_public_key(own_key, algorithm)?;
+    }
+
+    let body = read_input(&args.body)?;
+    let mut lines = args.headers.clone();
+    if let Some(path) = &args.headers_file {
+        lines.extend(read_input(path)?.lines().map(str::to_string));
+    }
+    let headers = parse_headers(&lines)?;
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let timestamp = header("timestamp");
+    let timezone = header("timezone");
+
+    println!("merchant_id: {}", credentials.merchant_id);
+    println!("timestamp:   {}", describe_timestamp(timestamp));
+    println!("timezone:    {}", describe_timezone(timezone));
+    println!("body:        {} bytes", body.len());
+    if let (Some(timestamp), Some(timezone)) = (timestamp, timezone) {
+        println!("canonical string:");
+        println!("{}", credentials.signing_string(timestamp, timezone, &body));
+    }
+    println!();
+
+    match credentials.verify_request(&headers, &body) {
+        Ok(matched) => {
+            match matched {
+                MatchedKey::Primary => println!("signature is valid"),
+                MatchedKey::Rotation(key) => println!(
+                    "signature is valid, matched rotation key {}",
+                    key.key_id.as_deref().unwrap_or("(no key id)")
+                ),
+            }
+            Ok(())
+        }
+        Err(failure) => Err(explain(&credentials, &body, &failure).into()),
+    }
+}
+
+/// Parses `name: value` lines, skipping blank lines and HTTP request or status lines.
+fn parse_headers(lines: &[String]) -> CliResult<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for line in lines.iter().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with("HTTP/") || line.contains(" HTTP/") {
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(format!("invalid header line {line:?}, expected `name: value`").into());
+        };
+        headers.append(
+            HeaderName::try_from(name.trim())?,
+            HeaderValue::try_from(value.trim())?,
+        );
+    }
+    Ok(headers)
+}
+
+fn describe_timezone(timezone: Option<&str>) -> String {
+    match timezone {
+        Some(timezone) if timezone.parse::<chrono_tz::Tz>().is_ok() => timezone.to_string(),
+        Some(timezone) => format!("{timezone} (not an IANA timezone)"),
+        None => "(missing)".to_string(),
+    }
+}
+
+fn describe_timestamp(timestamp: Option<&str>) -> String {
+    let Some(timestamp) = timestamp else {
+        return "(missing)".to_string();
+    };
+    let parsed = timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis);
+
+    match parsed {
+        Some(time) => {
+            let age = chrono::Utc::now() - time;
+            format!("{timestamp} ({time}, {}s ago)", age.num_seconds())
+        }
+        None => format!("{timestamp} (not a timestamp in milliseconds)"),
+    }
+}
+
+/// Describes which part of the verification failed, with the usual causes.
+fn explain(credentials: &Credentials, body: &str, failure: &ApiFailure) -> String {
+    let reason = match failure {
+        ApiFailure::MissingHeader(name) => {
+            format!("the `{name}` header is missing; pass it with `--header '{name}: ...'`")
+        }
+        ApiFailure::InvalidSignature(VerifySignatureError::Base64(_)) => {
+            "the `signature` header is not valid base64; check that it was not truncated or \
+             url-encoded"
+                .to_string()
+        }
+        ApiFailure::InvalidSignature(VerifySignatureError::SignatureFormat(_)) => {
+            "the signature is malformed for this key and algorithm; check that the public key \
+             and `--dd-algorithm` are the right ones"
+                .to_string()
+        }
+        ApiFailure::InvalidSignature(
+            VerifySignatureError::Verification(_) | VerifySignatureError::NoKeyMatched(_),
+        ) => {
+            let mut causes = vec![
+                "the body differs from what was signed (re-serialized JSON, whitespace, encoding)",
+                "the merchant id differs from the one that was signed",
+                "the public key does not match the key that signed",
+                "the signature algorithm differs from the one that signed (`--dd-algorithm`)",
+            ];
+            if body.ends_with('\n') {
+                causes.insert(
+                    0,
+                    "the body ends with a newline, which may not have been signed",
+                );
+            }
+            let own_key = credentials.private_key().to_public_key();
+            if own_key != *credentials.dd_public_key() {
+                causes.push("the message was signed by this merchant; try `--own-key`");
+            }
+            format!(
+                "the signature does not match the canonical string. Likely causes:\n  - {}",
+                causes.join("\n  - ")
+            )
+        }
+        failure => failure.to_string(),
+    };
+
+    format!("verification failed: {reason}")
+}
@@ -1,7 +1,7 @@
 use crate::endpoints::payment::request::{CreatePaymentRequest, QueryPaymentRequest};
 use crate::endpoints::payment::response::{CreatePaymentResponse, QueryPaymentResponse};
-use crate::framework::endpoint::{EndpointSpec, RequestBody};
-use crate::framework::response::ApiResponse;
+use crate::framework::endpoint::{EndpointSpec, RequestBody, json_body};
+use crate::framework::response::{ApiFailure, ApiResponse};
 use http::Method;
 
 impl EndpointSpec for CreatePaymentRequest {
@@ -16,8 +16,8 @@ impl EndpointSpec for CreatePaymentRequest {
     }
 
     #[inline]
-    fn body(&self) -> Option<RequestBody> {
-        Some(RequestBody::Json(serde_json::to_string(self).unwrap()))
+    fn body(&self) -> Result<Option<RequestBody<'_>>, ApiFailure> {
+        json_body(self)
     }
 }
 
@@ -32,8 +32,12 @@ impl EndpointSpec for QueryPaymentRequest {
         "payment/result".into()
     }
 
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
     #[inline]
-    fn body(&self) -> Option<RequestBody> {
-        Some(RequestBody::Json(serde_json::to_string(self).unwrap()))
+    fn body(&self) -> Result<Option<RequestBody<'_>>, ApiFailure> {
+        json_body(self)
     }
 }
@@ -1,8 +1,12 @@
 mod endpoint;
+pub mod notification;
+pub mod redirect;
 pub mod request;
 pub mod response;
 pub mod schema;
 
+use crate::endpoints::payment::schema::{AmountUnit, PaymentStatus};
+
 #[derive(Debug, thiserror::Error)]
 pub enum AmountUnitFloatError {
     #[error("amount must be finite (not NaN/Inf)")]
@@ -10,3 +14,22 @@ pub enum AmountUnitFloatError {
     #[error("amount must be non-negative")]
     Negative,
 }
+
+/// Why an [`OrderSummary`](schema::OrderSummary) cannot follow another one.
+#[derive(Debug, thiserror::Error)]
+pub enum TransitionError {
+    #[error("snapshots of different orders: {0} and {1}")]
+    DifferentOrder(String, String),
+
+    #[error("snapshot updated at {1} is older than the one updated at {0}")]
+    OutOfOrder(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>),
+
+    #[error("illegal payment status transition from {0:?} to {1:?}")]
+    Status(PaymentStatus, PaymentStatus),
+
+    #[error("refunded amount decreased from {0:?} to {1:?}")]
+    RefundDecreased(AmountUnit, AmountUnit),
+
+    #[error("refunded amount {0:?} exceeds the payment amount {1:?}")]
+    RefundExceedsAmount(AmountUnit, AmountUnit),
+}
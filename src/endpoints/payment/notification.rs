@@ -0,0 +1,32 @@
+use crate::endpoints::payment::schema::OrderSummary;
+use crate::framework::response::ApiFailure;
+use crate::framework::signature::Credentials;
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// A payment notification posted by DianDianPay to the merchant's notification URL when the
+/// status of an order changes.
+///
+/// Use [`Self::from_request`] to verify and parse one.
+///
+/// Both the `{ "order": ... }` payload and signing notifications like API responses are
+/// [unverified](crate#unverified-wire-details).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentNotification {
+    /// The order, as of the status change.
+    pub order: OrderSummary,
+}
+
+impl PaymentNotification {
+    /// Verifies the signature headers of a received notification against DianDianPay's public
+    /// key, then parses its body.
+    pub fn from_request(
+        credentials: &Credentials,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Result<PaymentNotification, ApiFailure> {
+        credentials.verify_request(headers, body)?;
+
+        Ok(serde_json::from_str(body)?)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::endpoints::payment::request::QueryPaymentRequest;
+use crate::endpoints::payment::schema::PaymentStatus;
+use std::collections::BTreeMap;
+use url::Url;
+
+/// The query parameter carrying the merchant order id on return URLs.
+pub const MERCHANT_ORDER_ID_PARAM: &str = "merchant_order_id";
+
+/// The query parameter carrying the DianDianPay order id on return URLs.
+/// [Unverified](crate#unverified-wire-details).
+pub const ID_PARAM: &str = "id";
+
+/// The query parameter carrying the payment status on return URLs.
+/// [Unverified](crate#unverified-wire-details).
+pub const PAYMENT_STATUS_PARAM: &str = "payment_status";
+
+/// Builds the `redirect_url` of a
+/// [`CreatePaymentRequest`](crate::endpoints::payment::request::CreatePaymentRequest), carrying
+/// the parameters needed to find the order when the customer comes back.
+///
+/// ```
+/// use diandianpay_rs::endpoints::payment::redirect::ReturnUrl;
+///
+/// let url = ReturnUrl::new("https://shop.example/checkout/done".parse().unwrap(), "order-1")
+///     .param("cart", "42")
+///     .build();
+/// assert_eq!(
+///     url.as_str(),
+///     "https://shop.example/checkout/done?merchant_order_id=order-1&cart=42"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReturnUrl {
+    url: Url,
+}
+
+impl ReturnUrl {
+    /// A return URL on `base`, identifying the order by `merchant_order_id`.
+    pub fn new(base: Url, merchant_order_id: &str) -> Self {
+        ReturnUrl { url: base }.param(MERCHANT_ORDER_ID_PARAM, merchant_order_id)
+    }
+
+    /// Adds a query parameter, e.g. a session or cart id to correlate the return with.
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        self.url.query_pairs_mut().append_pair(name, value);
+        self
+    }
+
+    pub fn build(self) -> Url {
+        self.url
+    }
+}
+
+impl From<ReturnUrl> for String {
+    fn from(url: ReturnUrl) -> Self {
+        url.build().into()
+    }
+}
+
+/// The query parameters of a request to a return URL, as DianDianPay sends the customer back.
+///
+/// They come from the customer's browser and are not signed: use [`Self::query_request`] to
+/// fetch the actual state of the order before acting on it.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReturnParams {
+    /// The merchant order id, as set by [`ReturnUrl`].
+    pub merchant_order_id: Option<String>,
+
+    /// The DianDianPay order id, if appended. Informational only, see [`Self::query_request`].
+    pub id: Option<String>,
+
+    /// The payment status, if appended.
+    pub payment_status: Option<PaymentStatus>,
+
+    /// Every other parameter, including the ones added with [`ReturnUrl::param`].
+    pub extra: BTreeMap<String, String>,
+}
+
+impl ReturnParams {
+    /// Parses a URL-encoded query string, without the leading `?`.
+    pub fn from_query(query: &str) -> Self {
+        let mut params = ReturnParams::default();
+
+        for (name, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let value = value.into_owned();
+            match name.as_ref() {
+                MERCHANT_ORDER_ID_PARAM => params.merchant_order_id = Some(value),
+                ID_PARAM => params.id = Some(value),
+                PAYMENT_STATUS_PARAM => params.payment_status = Some(value.into()),
+                _ => {
+                    params.extra.insert(name.into_owned(), value);
+                }
+            }
+        }
+
+        params
+    }
+
+    /// Parses the query of the URL the customer came back to.
+    pub fn from_url(url: &Url) -> Self {
+        ReturnParams::from_query(url.query().unwrap_or_default())
+    }
+
+    /// The request to look the order up with, by merchant order id. `None` when it was not sent
+    /// back.
+    ///
+    /// The DianDianPay `id` is never used: the customer can change it to point at another order.
+    /// Check that the merchant order id belongs to the customer's session, e.g. with a parameter
+    /// added by [`ReturnUrl::param`].
+    pub fn query_request(&self, merchant_id: &str) -> Option<QueryPaymentRequest> {
+        let merchant_order_id = self.merchant_order_id.as_ref()?;
+
+        Some(
+            QueryPaymentRequest::builder()
+                .merchant_id(merchant_id)
+                .merchant_order_id(merchant_order_id)
+                .build(),
+        )
+    }
+}
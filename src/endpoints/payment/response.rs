@@ -1,8 +1,7 @@
 use crate::endpoints::payment::schema::{AmountUnit, NextAction, OrderSummary};
-use crate::framework::response::{ApiResponse, JsonResult};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatePaymentResponse {
     /// DianDianPay System Unique Identifier
     pub id: String,
@@ -19,13 +18,12 @@ pub struct CreatePaymentResponse {
     /// Currency code, the value is a three digit currency code that follows the ISO 4217 standard.
     pub currency: String,
 
-    pub next_action: Option<NextAction>,
+    /// What the customer must do to complete the payment.
+    #[serde(default)]
+    pub next_action: NextAction,
 }
 
-impl JsonResult for ApiResponse<CreatePaymentResponse> {}
-
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryPaymentResponse {
     pub order: OrderSummary,
 }
-impl JsonResult for ApiResponse<QueryPaymentResponse> {}
@@ -1,27 +1,29 @@
-use crate::endpoints::payment::AmountUnitFloatError;
+use crate::endpoints::payment::{AmountUnitFloatError, TransitionError};
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, strum_macros::AsRefStr)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
 #[serde(rename_all = "UPPERCASE")]
 #[allow(missing_docs)]
 pub enum PaymentType {
     Card,
     Paypal,
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, strum_macros::AsRefStr)]
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
 #[allow(missing_docs)]
 pub enum DeliveryMethodType {
     #[serde(rename = "Pharmaceutical")]
     Physical,
     #[serde(rename = "DIGITAL")]
     Digital,
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(
-    Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, strum_macros::AsRefStr,
-)]
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
 #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
 #[allow(missing_docs)]
 pub enum TerminalType {
@@ -30,6 +32,8 @@ pub enum TerminalType {
     Wap,
     App,
     MiniApp,
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
@@ -44,15 +48,220 @@ pub enum PaymentStatus {
     Custom(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NextAction {
-    /// Redirect the customer to this URL to complete
-    /// further actions (such as payment or providing additional information)
-    pub redirect_url: String,
+impl From<&str> for PaymentStatus {
+    /// Parses the lowercase wire name of a status, keeping any other value as a custom status.
+    fn from(status: &str) -> Self {
+        match status {
+            "succeeded" => PaymentStatus::Succeeded,
+            "pending" => PaymentStatus::Pending,
+            "refunded" => PaymentStatus::Refunded,
+            "failed" => PaymentStatus::Failed,
+            status => PaymentStatus::Custom(status.to_string()),
+        }
+    }
+}
 
-    /// The URL to redirect the customer back to the merchant
-    /// page after completing or canceling the action
-    pub return_url: String,
+impl From<String> for PaymentStatus {
+    fn from(status: String) -> Self {
+        match PaymentStatus::from(status.as_str()) {
+            PaymentStatus::Custom(_) => PaymentStatus::Custom(status),
+            known => known,
+        }
+    }
+}
+
+impl PaymentStatus {
+    /// Whether no further status change is expected. Custom statuses are never terminal.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, PaymentStatus::Refunded | PaymentStatus::Failed)
+    }
+
+    /// Whether an order can move from this status to `next`.
+    ///
+    /// Payments go from `Pending` to `Succeeded` or `Failed`, and from `Succeeded` to `Refunded`.
+    /// Staying in the same status is allowed, as notifications can be repeated. Transitions from or
+    /// to a custom status are always allowed, as nothing is known about them.
+    pub fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        use PaymentStatus::*;
+
+        match (self, next) {
+            (Custom(_), _) | (_, Custom(_)) => true,
+            (previous, next) if previous == next => true,
+            (Pending, Succeeded | Failed) => true,
+            (Succeeded, Refunded) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The state of an order, derived from its [`PaymentStatus`] and refunded amount.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum PaymentState {
+    Pending,
+    Succeeded,
+    /// Succeeded, and refunded for less than the full amount.
+    PartiallyRefunded,
+    /// Reported as refunded, or succeeded and refunded for the full amount.
+    Refunded,
+    Failed,
+    Custom(String),
+}
+
+/// What the customer must do to complete a created payment.
+///
+/// A missing or `null` `next_action` is [`NextAction::None`]. An action without a `type` is a
+/// redirect, as returned by older API versions.
+///
+/// The `type` values `REDIRECT`, `THREE_DS_CHALLENGE` and `NONE` are
+/// [unverified](crate#unverified-wire-details), so any other value is kept as
+/// [`NextAction::Unknown`].
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Option<NextActionRepr>", into = "NextActionRepr")]
+pub enum NextAction {
+    /// Nothing to do, the payment was processed without the customer.
+    #[default]
+    None,
+
+    /// Redirect the customer to `redirect_url` to complete further actions (such as payment or
+    /// providing additional information). They are sent back to `return_url` after completing or
+    /// canceling the action.
+    Redirect {
+        redirect_url: String,
+        return_url: String,
+    },
+
+    /// Redirect the customer to their card issuer's 3-D Secure challenge page at `redirect_url`.
+    /// They are sent back to `return_url` once authenticated.
+    ThreeDsChallenge {
+        redirect_url: String,
+        return_url: String,
+    },
+
+    /// An action type this crate does not know about.
+    Unknown {
+        kind: String,
+        redirect_url: Option<String>,
+        return_url: Option<String>,
+    },
+}
+
+impl NextAction {
+    /// Where to send the customer, if anywhere.
+    pub fn redirect_url(&self) -> Option<&str> {
+        match self {
+            NextAction::None => None,
+            NextAction::Redirect { redirect_url, .. }
+            | NextAction::ThreeDsChallenge { redirect_url, .. } => Some(redirect_url),
+            NextAction::Unknown { redirect_url, .. } => redirect_url.as_deref(),
+        }
+    }
+
+    /// Where the customer comes back to, if anywhere.
+    pub fn return_url(&self) -> Option<&str> {
+        match self {
+            NextAction::None => None,
+            NextAction::Redirect { return_url, .. }
+            | NextAction::ThreeDsChallenge { return_url, .. } => Some(return_url),
+            NextAction::Unknown { return_url, .. } => return_url.as_deref(),
+        }
+    }
+
+    /// Whether the customer must be redirected to complete the payment.
+    pub fn requires_redirect(&self) -> bool {
+        self.redirect_url().is_some()
+    }
+}
+
+/// The wire shape of [`NextAction`].
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Deserialize)]
+struct NextActionRepr {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    redirect_url: Option<String>,
+    return_url: Option<String>,
+}
+
+impl TryFrom<Option<NextActionRepr>> for NextAction {
+    type Error = String;
+
+    fn try_from(repr: Option<NextActionRepr>) -> Result<Self, Self::Error> {
+        repr.map_or(Ok(NextAction::None), NextAction::try_from)
+    }
+}
+
+impl TryFrom<NextActionRepr> for NextAction {
+    type Error = String;
+
+    fn try_from(repr: NextActionRepr) -> Result<Self, Self::Error> {
+        let NextActionRepr {
+            kind,
+            redirect_url,
+            return_url,
+        } = repr;
+        let urls = |kind: &str, redirect_url, return_url| match (redirect_url, return_url) {
+            (Some(redirect_url), Some(return_url)) => Ok((redirect_url, return_url)),
+            _ => Err(format!(
+                "{kind} next action needs a redirect_url and a return_url"
+            )),
+        };
+
+        match kind.as_deref() {
+            None if redirect_url.is_none() && return_url.is_none() => Ok(NextAction::None),
+            Some("NONE") => Ok(NextAction::None),
+            None | Some("REDIRECT") => {
+                let (redirect_url, return_url) = urls("REDIRECT", redirect_url, return_url)?;
+                Ok(NextAction::Redirect {
+                    redirect_url,
+                    return_url,
+                })
+            }
+            Some("THREE_DS_CHALLENGE") => {
+                let (redirect_url, return_url) =
+                    urls("THREE_DS_CHALLENGE", redirect_url, return_url)?;
+                Ok(NextAction::ThreeDsChallenge {
+                    redirect_url,
+                    return_url,
+                })
+            }
+            Some(kind) => Ok(NextAction::Unknown {
+                kind: kind.to_string(),
+                redirect_url,
+                return_url,
+            }),
+        }
+    }
+}
+
+impl From<NextAction> for NextActionRepr {
+    fn from(action: NextAction) -> Self {
+        let (kind, redirect_url, return_url) = match action {
+            NextAction::None => ("NONE".to_string(), None, None),
+            NextAction::Redirect {
+                redirect_url,
+                return_url,
+            } => ("REDIRECT".to_string(), Some(redirect_url), Some(return_url)),
+            NextAction::ThreeDsChallenge {
+                redirect_url,
+                return_url,
+            } => (
+                "THREE_DS_CHALLENGE".to_string(),
+                Some(redirect_url),
+                Some(return_url),
+            ),
+            NextAction::Unknown {
+                kind,
+                redirect_url,
+                return_url,
+            } => (kind, redirect_url, return_url),
+        };
+
+        NextActionRepr {
+            kind: Some(kind),
+            redirect_url,
+            return_url,
+        }
+    }
 }
 
 #[serde_with::skip_serializing_none]
@@ -403,8 +612,9 @@ pub enum PaymentDataSummary {
     Paypal(PaymentPaypalSummary),
     #[serde(untagged)]
     Card(PaymentCardSummary),
-    // #[serde(untagged)]
-    // Unknown(serde_json::Value),
+    /// Payment data of a shape this crate does not know yet.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -465,6 +675,73 @@ pub struct OrderSummary {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl OrderSummary {
+    /// The state of the order, telling partial refunds apart from full ones.
+    ///
+    /// A `Refunded` status is always [`PaymentState::Refunded`], whatever the refunded amount, as
+    /// [`PaymentStatus::is_terminal`] says no further change follows it. Refunds of a `Succeeded`
+    /// order are told apart by the refunded amount.
+    pub fn state(&self) -> PaymentState {
+        let refunded = self.refunded_amount.clone().unwrap_or_default();
+
+        match &self.payment_status {
+            PaymentStatus::Pending => PaymentState::Pending,
+            PaymentStatus::Failed => PaymentState::Failed,
+            PaymentStatus::Refunded => PaymentState::Refunded,
+            PaymentStatus::Succeeded if refunded == AmountUnit(0) => PaymentState::Succeeded,
+            PaymentStatus::Succeeded if refunded < self.gross_amount.value => {
+                PaymentState::PartiallyRefunded
+            }
+            PaymentStatus::Succeeded => PaymentState::Refunded,
+            PaymentStatus::Custom(status) => PaymentState::Custom(status.clone()),
+        }
+    }
+
+    /// Whether [`Self::state`] is final: refunded or failed. This holds whenever
+    /// [`PaymentStatus::is_terminal`] does, and for a `Succeeded` order refunded in full.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.state(), PaymentState::Refunded | PaymentState::Failed)
+    }
+
+    /// Checks that `next` is a valid later snapshot of this order: the same order, a legal status
+    /// transition, not updated earlier, and a refunded amount that only grows up to the paid
+    /// amount.
+    pub fn validate_transition(&self, next: &OrderSummary) -> Result<(), TransitionError> {
+        if self.id != next.id {
+            return Err(TransitionError::DifferentOrder(
+                self.id.clone(),
+                next.id.clone(),
+            ));
+        }
+        if next.update_at < self.update_at {
+            return Err(TransitionError::OutOfOrder(self.update_at, next.update_at));
+        }
+        if !self.payment_status.can_transition_to(&next.payment_status) {
+            return Err(TransitionError::Status(
+                self.payment_status.clone(),
+                next.payment_status.clone(),
+            ));
+        }
+
+        let previous_refund = self.refunded_amount.clone().unwrap_or_default();
+        let next_refund = next.refunded_amount.clone().unwrap_or_default();
+        if next_refund < previous_refund {
+            return Err(TransitionError::RefundDecreased(
+                previous_refund,
+                next_refund,
+            ));
+        }
+        if next_refund > next.gross_amount.value {
+            return Err(TransitionError::RefundExceedsAmount(
+                next_refund,
+                next.gross_amount.value.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 impl From<(&str, AmountUnit)> for Amount {
     fn from(value: (&str, AmountUnit)) -> Self {
         Self {
@@ -474,7 +751,7 @@ impl From<(&str, AmountUnit)> for Amount {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct AmountUnit(u64);
 
@@ -553,3 +830,125 @@ pub struct PaymentCardSummary {
     #[serde(rename = "last4")]
     pub last_four: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PaymentStatus::*;
+
+    fn order(status: PaymentStatus, refunded: Option<u64>) -> OrderSummary {
+        let mut order: OrderSummary = serde_json::from_value(serde_json::json!({
+            "id": "P1",
+            "merchant_order_id": "O1",
+            "payment_method": { "payment_data": {}, "payment_type": "CARD" },
+            "payment_status": "pending",
+            "payment_amount": { "currency": "USD", "value": 100 },
+            "update_at": "2026-01-01T00:00:00Z",
+            "created_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        order.payment_status = status;
+        order.refunded_amount = refunded.map(AmountUnit);
+        order
+    }
+
+    #[test]
+    fn status_transitions() {
+        let statuses = [Pending, Succeeded, Refunded, Failed];
+        let allowed = [
+            (Pending, Pending),
+            (Pending, Succeeded),
+            (Pending, Failed),
+            (Succeeded, Succeeded),
+            (Succeeded, Refunded),
+            (Refunded, Refunded),
+            (Failed, Failed),
+        ];
+
+        for previous in &statuses {
+            for next in &statuses {
+                let expected = allowed.contains(&(previous.clone(), next.clone()));
+                assert_eq!(
+                    previous.can_transition_to(next),
+                    expected,
+                    "{previous:?} to {next:?}"
+                );
+            }
+
+            let custom = Custom("chargeback".into());
+            assert!(previous.can_transition_to(&custom));
+            assert!(custom.can_transition_to(previous));
+        }
+    }
+
+    #[test]
+    fn state() {
+        let cases = [
+            (Pending, None, PaymentState::Pending),
+            (Failed, None, PaymentState::Failed),
+            (Succeeded, None, PaymentState::Succeeded),
+            (Succeeded, Some(0), PaymentState::Succeeded),
+            (Succeeded, Some(40), PaymentState::PartiallyRefunded),
+            (Succeeded, Some(100), PaymentState::Refunded),
+            (Refunded, None, PaymentState::Refunded),
+            (Refunded, Some(0), PaymentState::Refunded),
+            (Refunded, Some(40), PaymentState::Refunded),
+            (Refunded, Some(100), PaymentState::Refunded),
+            (
+                Custom("chargeback".into()),
+                None,
+                PaymentState::Custom("chargeback".into()),
+            ),
+        ];
+
+        for (status, refunded, expected) in cases {
+            let order = order(status.clone(), refunded);
+            assert_eq!(order.state(), expected, "{status:?} with {refunded:?}");
+            if status.is_terminal() {
+                assert!(order.is_terminal(), "{status:?} with {refunded:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn validate_transition() {
+        let pending = order(Pending, None);
+        let succeeded = order(Succeeded, None);
+        let partial = order(Succeeded, Some(40));
+
+        assert!(pending.validate_transition(&succeeded).is_ok());
+        assert!(succeeded.validate_transition(&partial).is_ok());
+        assert!(
+            partial
+                .validate_transition(&order(Refunded, Some(100)))
+                .is_ok()
+        );
+
+        assert!(matches!(
+            succeeded.validate_transition(&pending),
+            Err(TransitionError::Status(Succeeded, Pending))
+        ));
+        assert!(matches!(
+            partial.validate_transition(&succeeded),
+            Err(TransitionError::RefundDecreased(..))
+        ));
+        assert!(matches!(
+            partial.validate_transition(&order(Refunded, Some(101))),
+            Err(TransitionError::RefundExceedsAmount(..))
+        ));
+
+        let mut other = order(Succeeded, None);
+        other.id = "P2".into();
+        assert!(matches!(
+            pending.validate_transition(&other),
+            Err(TransitionError::DifferentOrder(..))
+        ));
+
+        let mut older = order(Succeeded, None);
+        older.update_at = pending.update_at - chrono::Duration::seconds(1);
+        assert!(matches!(
+            pending.validate_transition(&older),
+            Err(TransitionError::OutOfOrder(..))
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::endpoints::payment::request::{CreatePaymentRequest, QueryPaymentRequest};
+use crate::endpoints::payment::response::{CreatePaymentResponse, QueryPaymentResponse};
+use crate::framework::client::async_api::Client;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::framework::client::blocking_api::HttpApiClient;
+use crate::framework::endpoint::EndpointSpec;
+use crate::framework::response::{ApiResponse, ApiResult};
+use std::future::Future;
+
+/// The operations of the async [`Client`], as a trait so application code can be generic over it
+/// and be tested against a fake.
+pub trait ApiClient: Send + Sync {
+    /// Issue an API request of the given type.
+    fn request<Endpoint>(
+        &self,
+        endpoint: &Endpoint,
+    ) -> impl Future<Output = ApiResult<Endpoint::ResponseType>> + Send
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+        Endpoint::ResponseType: Send;
+
+    /// Creates a payment.
+    fn create_payment(
+        &self,
+        request: &CreatePaymentRequest,
+    ) -> impl Future<Output = ApiResult<ApiResponse<CreatePaymentResponse>>> + Send {
+        self.request(request)
+    }
+
+    /// Queries the result of a payment.
+    fn query_payment(
+        &self,
+        request: &QueryPaymentRequest,
+    ) -> impl Future<Output = ApiResult<ApiResponse<QueryPaymentResponse>>> + Send {
+        self.request(request)
+    }
+}
+
+/// The operations of the blocking `HttpApiClient`, as a trait so application code can be generic
+/// over it and be tested against a fake.
+pub trait BlockingApiClient {
+    /// Issue an API request of the given type.
+    fn request<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<Endpoint::ResponseType>
+    where
+        Endpoint: EndpointSpec + Send + Sync;
+
+    /// Creates a payment.
+    fn create_payment(
+        &self,
+        request: &CreatePaymentRequest,
+    ) -> ApiResult<ApiResponse<CreatePaymentResponse>> {
+        self.request(request)
+    }
+
+    /// Queries the result of a payment.
+    fn query_payment(
+        &self,
+        request: &QueryPaymentRequest,
+    ) -> ApiResult<ApiResponse<QueryPaymentResponse>> {
+        self.request(request)
+    }
+}
+
+impl ApiClient for Client {
+    fn request<Endpoint>(
+        &self,
+        endpoint: &Endpoint,
+    ) -> impl Future<Output = ApiResult<Endpoint::ResponseType>> + Send
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+        Endpoint::ResponseType: Send,
+    {
+        Client::request(self, endpoint)
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl BlockingApiClient for HttpApiClient {
+    fn request<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<Endpoint::ResponseType>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        HttpApiClient::request(self, endpoint)
+    }
+}
@@ -1,15 +1,17 @@
-use crate::framework::client::ClientConfig;
-use crate::framework::endpoint::{EndpointSpec, MultipartPart, RequestBody};
-use crate::framework::response::ApiResponseType;
-use crate::framework::signature::SignClient;
-use crate::framework::{
-    Environment,
-    response::ApiResult,
-    response::{ApiErrors, ApiFailure},
-    signature::Credentials,
+use crate::framework::client::builder::ClientBuilder;
+use crate::framework::client::interceptor::{
+    Intercept, Interceptor, Interceptors, RequestParts, ResponseParts,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::framework::client::retry::RetryPolicy;
+use crate::framework::client::{ClientConfig, parse_response, parse_response_with_meta};
+use crate::framework::endpoint::{EndpointSpec, OutgoingBody, OutgoingContent, OutgoingPart};
+use crate::framework::response::{ApiResponseType, ResponseWithMeta, StrictMode};
+use crate::framework::signature::{SignClient, signing_content};
+use crate::framework::{Environment, response::ApiResult, signature::Credentials};
 use std::borrow::Cow;
-use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 impl SignClient for reqwest::RequestBuilder {
     fn sign(mut self, credentials: &Credentials, body: &str) -> Self {
@@ -24,6 +26,9 @@ pub struct Client {
     environment: Environment,
     credentials: Credentials,
     http_client: reqwest::Client,
+    interceptors: Interceptors,
+    retry_policy: RetryPolicy,
+    strict_mode: StrictMode,
 }
 impl Client {
     pub fn new(
@@ -31,75 +36,209 @@ impl Client {
         config: ClientConfig,
         environment: Environment,
     ) -> Result<Client, crate::framework::Error> {
-        let mut builder = reqwest::Client::builder().default_headers(config.default_headers);
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // There is no resolve method in wasm.
-            if let Some(address) = config.resolve_ip {
-                let url = url::Url::from(&environment);
-                builder = builder.resolve(
-                    url.host_str()
-                        .expect("Environment url should have a hostname"),
-                    SocketAddr::new(address, 443),
-                );
-            }
-
-            // There are no timeouts in wasm. The property is documented as no-op in wasm32.
-            builder = builder.timeout(config.http_timeout);
-        }
-
-        let http_client = builder.build()?;
-
-        Ok(Client {
-            environment,
-            credentials,
-            http_client,
-        })
+        ClientBuilder::new(credentials, environment)
+            .config(config)
+            .build_async()
     }
 
     pub fn new_with_client(
         client: reqwest::Client,
         credentials: Credentials,
         environment: Environment,
+    ) -> Client {
+        Client::from_parts(
+            environment,
+            credentials,
+            client,
+            Interceptors::default(),
+            RetryPolicy::default(),
+            StrictMode::default(),
+        )
+    }
+
+    pub(crate) fn from_parts(
+        environment: Environment,
+        credentials: Credentials,
+        http_client: reqwest::Client,
+        interceptors: Interceptors,
+        retry_policy: RetryPolicy,
+        strict_mode: StrictMode,
     ) -> Client {
         Client {
             environment,
             credentials,
-            http_client: client,
+            http_client,
+            interceptors,
+            retry_policy,
+            strict_mode,
         }
     }
 
+    /// Adds an interceptor to the request pipeline. Interceptors run in the order they are added.
+    pub fn with_interceptor<I: Interceptor + 'static>(mut self, interceptor: I) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
     /// Issue an API request of the given type.
     pub async fn request<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<Endpoint::ResponseType>
     where
         Endpoint: EndpointSpec + Send + Sync,
         Endpoint::ResponseType: ApiResponseType + Send,
     {
-        // Build the request
+        let (response, _) = self.send(endpoint).await?;
+
+        parse_response(&response, &self.strict_mode, &endpoint.path())
+    }
+
+    /// Issue an API request of the given type, keeping the HTTP status, headers, latency and raw
+    /// body of the response next to the result.
+    ///
+    /// Fails only when no response was received. Failed responses, such as a non-successful status
+    /// or a body that cannot be decoded, are returned in `result` with their metadata.
+    pub async fn request_with_meta<Endpoint>(
+        &self,
+        endpoint: &Endpoint,
+    ) -> ApiResult<ResponseWithMeta<ApiResult<Endpoint::ResponseType>>>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+        Endpoint::ResponseType: ApiResponseType + Send,
+    {
+        let (response, elapsed) = self.send(endpoint).await?;
+
+        Ok(parse_response_with_meta(
+            response,
+            elapsed,
+            &self.strict_mode,
+            &endpoint.path(),
+        ))
+    }
+
+    /// Sends the request, retrying according to the retry policy.
+    async fn send<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<(ResponseParts, Duration)>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        let idempotent = endpoint.is_idempotent();
+        let mut retry = 0;
+        loop {
+            let result = self.send_once(endpoint).await;
+            let should_retry = match &result {
+                Ok((response, _)) => self
+                    .retry_policy
+                    .should_retry_response(retry, response, idempotent),
+                Err(failure) => self
+                    .retry_policy
+                    .should_retry_failure(retry, failure, idempotent),
+            };
+            if !should_retry {
+                return result;
+            }
+
+            let backoff = self.retry_policy.backoff(retry);
+            // There are no timers in wasm; retry right away.
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::time::sleep(backoff).await;
+            #[cfg(target_arch = "wasm32")]
+            let _ = backoff;
+            retry += 1;
+        }
+    }
+
+    /// Builds, signs and sends the request, running it through the interceptors.
+    async fn send_once<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<(ResponseParts, Duration)>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        let (mut request, multipart) = self.build_request(endpoint).await?;
+        let mut parts = RequestParts {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(bytes::Bytes::copy_from_slice),
+            multipart,
+        };
+
+        let original_body = parts.body.clone();
+
+        let started = Instant::now();
+        let mut response = match self.interceptors.before_send(&mut parts)? {
+            Intercept::Respond(response) => response,
+            Intercept::Continue => {
+                *request.method_mut() = parts.method.clone();
+                *request.url_mut() = parts.url.clone();
+                *request.headers_mut() = parts.headers.clone();
+                if parts.body != original_body {
+                    *request.body_mut() = parts.body.clone().map(Into::into);
+                }
+
+                match self.execute(request).await {
+                    Ok(response) => response,
+                    Err(error) => {
+                        self.interceptors.on_error(&parts, &error);
+                        return Err(error);
+                    }
+                }
+            }
+        };
+
+        self.interceptors.after_receive(&parts, &mut response)?;
+
+        Ok((response, started.elapsed()))
+    }
+
+    /// Sends a request and reads its response.
+    async fn execute(&self, request: reqwest::Request) -> ApiResult<ResponseParts> {
+        let response = self.http_client.execute(request).await?;
+
+        Ok(ResponseParts {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?,
+        })
+    }
+
+    /// Builds and signs the request for an endpoint, returning it with its signed multipart parts.
+    async fn build_request<Endpoint>(
+        &self,
+        endpoint: &Endpoint,
+    ) -> ApiResult<(reqwest::Request, Vec<(String, String)>)>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        // Build and sign the request
+        let url = endpoint.url(&self.environment)?;
+        let body = endpoint.body()?.map(OutgoingBody::prepare_async);
+        let body = match body {
+            Some(body) => Some(body.await?),
+            None => None,
+        };
+        let body_content = body.as_ref().map(OutgoingBody::signing_content);
+        let multipart = body
+            .as_ref()
+            .map(OutgoingBody::signed_parts)
+            .unwrap_or_default();
+        let content = signing_content(url.query(), body_content.as_deref())?.to_string();
         let mut request = self
             .http_client
-            .request(endpoint.method(), endpoint.url(&self.environment));
+            .request(endpoint.method(), url)
+            .sign(&self.credentials, &content);
 
-        if let Some(body) = endpoint.body() {
+        if let Some(body) = body {
             match body {
-                RequestBody::Json(json) => {
-                    request = request.sign(&self.credentials, &json).body(json);
+                OutgoingBody::Json(json) => {
+                    request = request.body(json);
                 }
-                RequestBody::Raw(bytes) => {
+                OutgoingBody::Raw(bytes) => {
                     request = request.body(bytes);
                 }
-                RequestBody::MultiPart(multipart) => {
+                OutgoingBody::MultiPart(parts) => {
                     let mut form = reqwest::multipart::Form::new();
-                    for (name, part) in multipart.parts() {
-                        match part {
-                            MultipartPart::Text(text) => {
-                                form = form.text(name, text);
-                            }
-                            MultipartPart::Bytes(bytes) => {
-                                form = form.part(name, reqwest::multipart::Part::bytes(bytes));
-                            }
-                        }
+                    for part in parts {
+                        form = form.part(part.name.clone(), multipart_part(part)?);
                     }
                     request = request.multipart(form);
                 }
@@ -112,20 +251,30 @@ impl Client {
             }
         }
 
-        let response = request.send().await?;
-
-        let status = response.status();
-        if status.is_success() {
-            let full_bytes = response.bytes().await?;
+        Ok((request.build()?, multipart))
+    }
+}
 
-            // let text = String::from_utf8_lossy(&full_bytes);
-            // println!("{}", text);
+/// Turns a signed part into an async multipart part, streaming files.
+fn multipart_part(part: OutgoingPart) -> ApiResult<reqwest::multipart::Part> {
+    use reqwest::multipart::Part;
 
-            Endpoint::ResponseType::from_response(&full_bytes)
-        } else {
-            let parsed: Result<ApiErrors, reqwest::Error> = response.json().await;
-            let errors = parsed.unwrap_or_default();
-            Err(ApiFailure::Error(status, errors))
+    let mut file = match part.content {
+        OutgoingContent::Text(text) => Part::text(text),
+        OutgoingContent::Bytes(bytes) => Part::bytes(bytes),
+        #[cfg(not(target_arch = "wasm32"))]
+        OutgoingContent::File(file) => {
+            let (reader, length) = file.into_async_reader();
+            let stream = tokio_util::io::ReaderStream::new(reader);
+            Part::stream_with_length(reqwest::Body::wrap_stream(stream), length)
         }
+    };
+
+    if let Some(file_name) = part.file_name {
+        file = file.file_name(file_name);
+    }
+    if let Some(mime_type) = part.mime_type {
+        file = file.mime_str(&mime_type)?;
     }
+    Ok(file)
 }
@@ -0,0 +1,161 @@
+use crate::framework::Environment;
+use crate::framework::client::async_api::Client;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+use crate::framework::client::blocking_api::HttpApiClient;
+use crate::framework::client::interceptor::{Interceptor, Interceptors};
+use crate::framework::client::retry::RetryPolicy;
+use crate::framework::client::{ClientConfig, configure_builder};
+use crate::framework::clock::Clock;
+use crate::framework::response::StrictMode;
+use crate::framework::signature::Credentials;
+use std::sync::Arc;
+
+/// Builds a [`Client`] or an `HttpApiClient` from the same set of options, so both stay
+/// equivalent.
+///
+/// ```
+/// # use diandianpay_rs::framework::{Environment, client::builder::ClientBuilder};
+/// # use diandianpay_rs::framework::client::retry::RetryPolicy;
+/// # use diandianpay_rs::framework::signature::Credentials;
+/// # fn credentials() -> Credentials {
+/// #     let key = rsa::RsaPrivateKey::new(&mut rand::rngs::OsRng, 1024).unwrap();
+/// #     Credentials::new("merchant", key.clone(), key.to_public_key())
+/// # }
+/// let client = ClientBuilder::new(credentials(), Environment::Test)
+///     .retry_policy(RetryPolicy::with_max_retries(2))
+///     .build_async()?;
+/// # Ok::<(), diandianpay_rs::framework::Error>(())
+/// ```
+pub struct ClientBuilder {
+    credentials: Credentials,
+    environment: Environment,
+    config: ClientConfig,
+    retry_policy: RetryPolicy,
+    interceptors: Interceptors,
+    strict_mode: StrictMode,
+    async_transport: Option<reqwest::Client>,
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    blocking_transport: Option<reqwest::blocking::Client>,
+}
+
+impl ClientBuilder {
+    pub fn new(credentials: Credentials, environment: Environment) -> ClientBuilder {
+        ClientBuilder {
+            credentials,
+            environment,
+            config: ClientConfig::default(),
+            retry_policy: RetryPolicy::default(),
+            interceptors: Interceptors::default(),
+            strict_mode: StrictMode::default(),
+            async_transport: None,
+            #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+            blocking_transport: None,
+        }
+    }
+
+    /// Sets the HTTP configuration. Ignored when a transport is set.
+    pub fn config(mut self, config: ClientConfig) -> ClientBuilder {
+        self.config = config;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> ClientBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Adds an interceptor to the request pipeline. Interceptors run in the order they are added.
+    pub fn interceptor<I: Interceptor + 'static>(mut self, interceptor: I) -> ClientBuilder {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Uses `clock` for the timestamp of signed requests, see [`Credentials::with_clock`].
+    pub fn clock(mut self, clock: impl Clock + 'static) -> ClientBuilder {
+        self.credentials = self.credentials.with_clock(clock);
+        self
+    }
+
+    /// Reports response fields this crate does not know about. Disabled by default.
+    pub fn strict_mode(mut self, strict_mode: StrictMode) -> ClientBuilder {
+        self.strict_mode = strict_mode;
+        self
+    }
+
+    /// Uses an existing `reqwest::Client` for [`Self::build_async`] instead of building one from
+    /// the config.
+    pub fn async_transport(mut self, client: reqwest::Client) -> ClientBuilder {
+        self.async_transport = Some(client);
+        self
+    }
+
+    /// Uses an existing `reqwest::blocking::Client` for [`Self::build_blocking`] instead of
+    /// building one from the config.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn blocking_transport(mut self, client: reqwest::blocking::Client) -> ClientBuilder {
+        self.blocking_transport = Some(client);
+        self
+    }
+
+    pub fn build_async(self) -> Result<Client, crate::framework::Error> {
+        let http_client = match self.async_transport {
+            Some(client) => client,
+            None => build_async_transport(self.config, &self.environment)?,
+        };
+
+        Ok(Client::from_parts(
+            self.environment,
+            self.credentials,
+            http_client,
+            self.interceptors,
+            self.retry_policy,
+            self.strict_mode,
+        ))
+    }
+
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn build_blocking(self) -> Result<HttpApiClient, crate::framework::Error> {
+        let http_client = match self.blocking_transport {
+            Some(client) => client,
+            None => {
+                let resolve = self.config.resolve_override(&self.environment)?;
+                configure_builder!(reqwest::blocking::Client::builder(), self.config, resolve)
+                    .build()?
+            }
+        };
+
+        Ok(HttpApiClient::from_parts(
+            self.environment,
+            self.credentials,
+            http_client,
+            self.interceptors,
+            self.retry_policy,
+            self.strict_mode,
+        ))
+    }
+}
+
+fn build_async_transport(
+    config: ClientConfig,
+    environment: &Environment,
+) -> Result<reqwest::Client, crate::framework::Error> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let builder = {
+        let resolve = config.resolve_override(environment)?;
+        let read_timeout = config.read_timeout;
+        let mut builder = configure_builder!(reqwest::Client::builder(), config, resolve);
+        if let Some(timeout) = read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        builder
+    };
+
+    // Only default headers are supported in wasm.
+    #[cfg(target_arch = "wasm32")]
+    let builder = {
+        let _ = environment;
+        reqwest::Client::builder().default_headers(config.default_headers)
+    };
+
+    Ok(builder.build()?)
+}
@@ -0,0 +1,328 @@
+use crate::framework::client::interceptor::{Intercept, Interceptor, RequestParts, ResponseParts};
+use crate::framework::response::ApiFailure;
+use base64::Engine;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use rsa::sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// JSON keys, and multipart part names, whose values are replaced by their digest before an
+/// interaction is stored.
+const SCRUBBED_KEYS: [&str; 5] = [
+    "card_number",
+    "cvv",
+    "card_holder_name",
+    "expiry_month",
+    "expiry_year",
+];
+/// Headers that are never stored.
+const SCRUBBED_HEADERS: [&str; 1] = ["signature"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum CassetteError {
+    #[error("could not access cassette {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+
+    #[error("invalid cassette {0}: {1}")]
+    Json(String, #[source] serde_json::Error),
+
+    #[error("no recorded interaction in cassette {0} matches {1} {2}")]
+    Unmatched(String, String, String),
+}
+
+/// A request as stored in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// The URL path and query, without the environment's scheme and host.
+    pub path: String,
+    /// The scrubbed body. JSON bodies are stored as JSON, anything else as a string.
+    pub body: Option<Value>,
+    /// The parts of a multipart body, which has no `body`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub multipart: Vec<RecordedPart>,
+}
+
+/// A multipart part as stored in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPart {
+    pub name: String,
+    /// The part's text, or the SHA-256 digest of its content. Scrubbed text parts are replaced by
+    /// their digest too.
+    pub value: String,
+}
+
+/// A response as stored in a cassette.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CassetteMode {
+    /// Send requests and append every interaction to the cassette file.
+    Record,
+    /// Answer requests from the cassette file without sending them.
+    Replay,
+}
+
+/// An [`Interceptor`] recording interactions to a JSON file, or replaying them offline.
+///
+/// Requests are matched on method, path and body; JSON bodies are compared after scrubbing, so key
+/// order does not matter, and multipart bodies by their part names and values. Each recorded
+/// interaction is replayed at most once.
+///
+/// Signatures are never written. Card numbers, CVVs, card holder names and expiry dates are
+/// replaced by their SHA-256 digest, so requests for different cards still match different
+/// interactions. Digests of short values such as CVVs are easily reversed by trying every value:
+/// keep cassettes out of public repositories.
+///
+/// Add the cassette as the first interceptor, so replayed responses skip the others' `before_send`
+/// hooks the same way real responses would.
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+impl Cassette {
+    /// Creates a cassette recording into `path`, replacing the file if it exists.
+    pub fn record(path: impl Into<PathBuf>) -> Cassette {
+        Cassette {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState {
+                interactions: Vec::new(),
+                used: Vec::new(),
+            }),
+        }
+    }
+
+    /// Loads a recorded cassette from `path` for replay.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Cassette, CassetteError> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+        let file: CassetteFile = serde_json::from_str(&content)
+            .map_err(|e| CassetteError::Json(path.display().to_string(), e))?;
+
+        Ok(Cassette {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                used: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+            }),
+        })
+    }
+
+    /// Replays `path` if it exists, and records into it otherwise.
+    pub fn record_or_replay(path: impl Into<PathBuf>) -> Result<Cassette, CassetteError> {
+        let path = path.into();
+        if path.exists() {
+            Cassette::replay(path)
+        } else {
+            Ok(Cassette::record(path))
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// The interactions recorded or loaded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().interactions.clone()
+    }
+
+    /// The number of loaded interactions that were not replayed yet.
+    pub fn unused_interactions(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .used
+            .iter()
+            .filter(|u| !**u)
+            .count()
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> Result<(), CassetteError> {
+        let file = CassetteFile {
+            interactions: interactions.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| CassetteError::Json(self.path.display().to_string(), e))?;
+        std::fs::write(&self.path, json).map_err(|e| io_error(&self.path, e))
+    }
+}
+
+impl Interceptor for Cassette {
+    fn before_send(&self, request: &mut RequestParts) -> Result<Intercept, ApiFailure> {
+        if self.mode == CassetteMode::Record {
+            return Ok(Intercept::Continue);
+        }
+
+        let recorded = record_request(request);
+        let mut state = self.state.lock().unwrap();
+        let CassetteState { interactions, used } = &mut *state;
+
+        let found = interactions
+            .iter()
+            .zip(used.iter_mut())
+            .find(|(interaction, used)| !**used && interaction.request == recorded);
+
+        match found {
+            Some((interaction, used)) => {
+                *used = true;
+                Ok(Intercept::Respond(replay_response(&interaction.response)))
+            }
+            None => Err(ApiFailure::Interceptor(Box::new(CassetteError::Unmatched(
+                self.path.display().to_string(),
+                recorded.method,
+                recorded.path,
+            )))),
+        }
+    }
+
+    fn after_receive(
+        &self,
+        request: &RequestParts,
+        response: &mut ResponseParts,
+    ) -> Result<(), ApiFailure> {
+        if self.mode == CassetteMode::Replay {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(Interaction {
+            request: record_request(request),
+            response: record_response(response),
+        });
+        state.used.push(true);
+
+        self.save(&state.interactions)
+            .map_err(|e| ApiFailure::Interceptor(Box::new(e)))
+    }
+}
+
+fn record_request(request: &RequestParts) -> RecordedRequest {
+    let mut path = request.url.path().to_string();
+    if let Some(query) = request.url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    RecordedRequest {
+        method: request.method.to_string(),
+        path,
+        body: request.body.as_deref().map(record_body),
+        multipart: request
+            .multipart
+            .iter()
+            .map(|(name, value)| RecordedPart {
+                name: name.clone(),
+                value: match SCRUBBED_KEYS.contains(&name.as_str()) {
+                    true => digest(value.as_bytes()),
+                    false => value.clone(),
+                },
+            })
+            .collect(),
+    }
+}
+
+fn record_body(body: &[u8]) -> Value {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut json) => {
+            scrub(&mut json);
+            json
+        }
+        Err(_) => Value::String(String::from_utf8_lossy(body).into_owned()),
+    }
+}
+
+fn record_response(response: &ResponseParts) -> RecordedResponse {
+    let headers = response
+        .headers
+        .iter()
+        .filter(|(name, _)| !SCRUBBED_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    let body = match serde_json::from_slice::<Value>(&response.body) {
+        Ok(mut json) => {
+            scrub(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(&response.body).into_owned(),
+    };
+
+    RecordedResponse {
+        status: response.status.as_u16(),
+        headers,
+        body,
+    }
+}
+
+fn replay_response(recorded: &RecordedResponse) -> ResponseParts {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &recorded.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::try_from(value.as_str()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+
+    ResponseParts {
+        status: StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK),
+        headers,
+        body: recorded.body.clone().into(),
+    }
+}
+
+fn scrub(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SCRUBBED_KEYS.contains(&key.as_str()) {
+                    *value = Value::String(digest(value.to_string().as_bytes()));
+                } else {
+                    scrub(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub),
+        _ => {}
+    }
+}
+
+/// The SHA-256 digest of a scrubbed value, base64-encoded and prefixed with `sha256:`.
+fn digest(value: &[u8]) -> String {
+    let digest = base64::engine::general_purpose::STANDARD.encode(Sha256::digest(value));
+    format!("sha256:{digest}")
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> CassetteError {
+    CassetteError::Io(path.display().to_string(), e)
+}
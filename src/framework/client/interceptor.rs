@@ -0,0 +1,123 @@
+use crate::framework::response::ApiFailure;
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode};
+use std::sync::Arc;
+use url::Url;
+
+/// A client-agnostic view of an outgoing request, after it has been signed.
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    /// The request body, if it is held in memory. Multipart bodies are not exposed; setting a body
+    /// on a multipart request replaces the form.
+    pub body: Option<Bytes>,
+    /// The name of each multipart part, with the value it contributes to the signature: its text,
+    /// or the SHA-256 digest of its content. Empty for other bodies. Changes are not applied.
+    pub multipart: Vec<(String, String)>,
+}
+
+/// A client-agnostic view of a received response, with its body fully read.
+#[derive(Debug, Clone)]
+pub struct ResponseParts {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// The outcome of an [`Interceptor::before_send`] hook.
+#[derive(Debug)]
+pub enum Intercept {
+    /// Send the (possibly modified) request.
+    Continue,
+    /// Do not send the request and use this response instead.
+    Respond(ResponseParts),
+}
+
+/// Hooks into the request pipeline of [`Client`](super::async_api::Client) and
+/// `HttpApiClient`.
+///
+/// Interceptors run in the order they were added. Returning an error from either hook aborts the
+/// request with that error.
+pub trait Interceptor: Send + Sync {
+    /// Called with the signed request right before it is sent. Changes to the method, URL,
+    /// headers and body are applied to the request. The signature is not updated, so changing what
+    /// was signed makes DianDianPay reject the request.
+    ///
+    /// Returning [`Intercept::Respond`] skips the network call and the remaining `before_send`
+    /// hooks.
+    fn before_send(&self, request: &mut RequestParts) -> Result<Intercept, ApiFailure> {
+        let _ = request;
+        Ok(Intercept::Continue)
+    }
+
+    /// Called with every response, including short-circuited ones, before it is parsed.
+    fn after_receive(
+        &self,
+        request: &RequestParts,
+        response: &mut ResponseParts,
+    ) -> Result<(), ApiFailure> {
+        let _ = (request, response);
+        Ok(())
+    }
+
+    /// Called when the request could not be sent or its response could not be read, e.g. on a
+    /// timeout or connection error. No response hooks run for such a request.
+    fn on_error(&self, request: &RequestParts, error: &ApiFailure) {
+        let _ = (request, error);
+    }
+}
+
+impl<T: Interceptor + ?Sized> Interceptor for Arc<T> {
+    fn before_send(&self, request: &mut RequestParts) -> Result<Intercept, ApiFailure> {
+        (**self).before_send(request)
+    }
+
+    fn after_receive(
+        &self,
+        request: &RequestParts,
+        response: &mut ResponseParts,
+    ) -> Result<(), ApiFailure> {
+        (**self).after_receive(request, response)
+    }
+
+    fn on_error(&self, request: &RequestParts, error: &ApiFailure) {
+        (**self).on_error(request, error)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: Arc<dyn Interceptor>) {
+        self.0.push(interceptor);
+    }
+
+    pub(crate) fn before_send(&self, request: &mut RequestParts) -> Result<Intercept, ApiFailure> {
+        for interceptor in &self.0 {
+            if let Intercept::Respond(response) = interceptor.before_send(request)? {
+                return Ok(Intercept::Respond(response));
+            }
+        }
+        Ok(Intercept::Continue)
+    }
+
+    pub(crate) fn after_receive(
+        &self,
+        request: &RequestParts,
+        response: &mut ResponseParts,
+    ) -> Result<(), ApiFailure> {
+        for interceptor in &self.0 {
+            interceptor.after_receive(request, response)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn on_error(&self, request: &RequestParts, error: &ApiFailure) {
+        for interceptor in &self.0 {
+            interceptor.on_error(request, error);
+        }
+    }
+}
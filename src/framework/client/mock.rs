@@ -0,0 +1,128 @@
+use crate::framework::Environment;
+use crate::framework::client::api::{ApiClient, BlockingApiClient};
+use crate::framework::client::interceptor::{RequestParts, ResponseParts};
+use crate::framework::client::parse_response;
+use crate::framework::endpoint::{EndpointSpec, RequestBody};
+use crate::framework::response::{ApiFailure, ApiResult, StrictMode};
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// A fake client returning queued responses, and recording the requests it was given.
+///
+/// Responses are parsed exactly like real ones, so queued bodies must be complete DianDianPay
+/// responses, including the `data` envelope. Requests are not signed.
+///
+/// # Panics
+///
+/// Requests panic when no response is queued.
+pub struct MockClient {
+    environment: Environment,
+    responses: Mutex<VecDeque<ApiResult<ResponseParts>>>,
+    requests: Mutex<Vec<RequestParts>>,
+}
+
+impl Default for MockClient {
+    fn default() -> Self {
+        MockClient::new(Environment::Test)
+    }
+}
+
+impl MockClient {
+    /// Creates a fake client building request URLs for `environment`.
+    pub fn new(environment: Environment) -> MockClient {
+        MockClient {
+            environment,
+            responses: Mutex::default(),
+            requests: Mutex::default(),
+        }
+    }
+
+    /// Queues a response with the given status and body.
+    pub fn push_response(&self, status: StatusCode, body: impl Into<Bytes>) {
+        self.push(Ok(ResponseParts {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }));
+    }
+
+    /// Queues a `200 OK` response with `body` serialized as JSON.
+    pub fn push_json<T: Serialize>(&self, body: &T) {
+        let body = serde_json::to_vec(body).expect("mock response should serialize");
+        self.push_response(StatusCode::OK, body);
+    }
+
+    /// Queues a failure, returned as is instead of a response.
+    pub fn push_failure(&self, failure: ApiFailure) {
+        self.push(Err(failure));
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RequestParts> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The number of queued responses that were not used yet.
+    pub fn pending_responses(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+
+    fn push(&self, response: ApiResult<ResponseParts>) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    fn respond<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<Endpoint::ResponseType>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        let body = match endpoint.body()? {
+            Some(RequestBody::Json(json)) => Some(Bytes::from(json)),
+            Some(RequestBody::Raw(bytes)) => Some(Bytes::from(bytes)),
+            Some(RequestBody::MultiPart(_)) | None => None,
+        };
+        let request = RequestParts {
+            method: endpoint.method(),
+            url: endpoint.url(&self.environment)?,
+            headers: HeaderMap::new(),
+            body,
+            multipart: Vec::new(),
+        };
+
+        let response = self.responses.lock().unwrap().pop_front();
+        let Some(response) = response else {
+            panic!(
+                "MockClient has no queued response for {} {}",
+                request.method, request.url
+            );
+        };
+        self.requests.lock().unwrap().push(request);
+
+        parse_response(&response?, &StrictMode::disabled(), &endpoint.path())
+    }
+}
+
+impl ApiClient for MockClient {
+    fn request<Endpoint>(
+        &self,
+        endpoint: &Endpoint,
+    ) -> impl Future<Output = ApiResult<Endpoint::ResponseType>> + Send
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+        Endpoint::ResponseType: Send,
+    {
+        std::future::ready(self.respond(endpoint))
+    }
+}
+
+impl BlockingApiClient for MockClient {
+    fn request<Endpoint>(&self, endpoint: &Endpoint) -> ApiResult<Endpoint::ResponseType>
+    where
+        Endpoint: EndpointSpec + Send + Sync,
+    {
+        self.respond(endpoint)
+    }
+}
@@ -1,30 +1,220 @@
-use std::net::IpAddr;
+use crate::framework::Environment;
+use crate::framework::response::{
+    ApiErrors, ApiFailure, ApiResponseType, ApiResult, RawBody, ResponseWithMeta, StrictMode,
+};
+use interceptor::ResponseParts;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
+pub mod api;
 pub mod async_api;
 // There is no blocking support for wasm.
 #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
 pub mod blocking_api;
+pub mod builder;
+#[cfg(feature = "cassette")]
+pub mod cassette;
+pub mod interceptor;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod retry;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+/// Which HTTP versions the client may use.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum HttpVersionPreference {
+    /// Let the client negotiate the version.
+    #[default]
+    Auto,
+    /// Only use HTTP/1.
+    Http1Only,
+    /// Use HTTP/2 without negotiating it first.
+    #[cfg(feature = "http2")]
+    Http2PriorKnowledge,
+}
 
 /// Configuration for the API client. Allows users to customize its behaviour.
+#[derive(Clone)]
 pub struct ClientConfig {
     /// The maximum time limit for an API request. If a request takes longer than this, it will be
     /// cancelled.
     /// Note: this configuration has no effect when the target is wasm32.
     pub http_timeout: Duration,
+    /// The maximum time limit for establishing a connection.
+    /// Note: this configuration has no effect when the target is wasm32.
+    pub connect_timeout: Option<Duration>,
+    /// The maximum time limit between two reads of the response.
+    /// Note: this configuration only applies to the async client, and has no effect when the
+    /// target is wasm32.
+    pub read_timeout: Option<Duration>,
     /// A default set of HTTP headers which will be sent with each API request.
     pub default_headers: http::HeaderMap,
-    /// A specific IP to use when establishing a connection
+    /// A specific IP to use when establishing a connection. The port is taken from the
+    /// environment URL.
     /// Note: this configuration has no effect when the target is wasm32.
     pub resolve_ip: Option<IpAddr>,
+    /// Proxies to send requests through, tried in order.
+    /// Note: this configuration has no effect when the target is wasm32.
+    pub proxies: Vec<reqwest::Proxy>,
+    /// Additional root certificates to trust, e.g. the CA of an intercepting proxy.
+    #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+    pub root_certificates: Vec<reqwest::Certificate>,
+    /// Whether to trust the built-in root certificates. Disable to only trust
+    /// `root_certificates`.
+    #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+    pub tls_built_in_root_certs: bool,
+    /// A client certificate to present for mutual TLS.
+    #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+    pub identity: Option<reqwest::Identity>,
+    /// How long idle connections are kept in the pool.
+    /// Note: this configuration has no effect when the target is wasm32.
+    pub pool_idle_timeout: Option<Duration>,
+    /// The maximum number of idle connections kept per host.
+    /// Note: this configuration has no effect when the target is wasm32.
+    pub pool_max_idle_per_host: Option<usize>,
+    /// Which HTTP versions to use.
+    /// Note: this configuration has no effect when the target is wasm32.
+    pub http_version: HttpVersionPreference,
 }
 
 impl Default for ClientConfig {
     fn default() -> Self {
         ClientConfig {
             http_timeout: Duration::from_secs(30),
+            connect_timeout: None,
+            read_timeout: None,
             default_headers: http::HeaderMap::default(),
             resolve_ip: None,
+            proxies: Vec::new(),
+            #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+            root_certificates: Vec::new(),
+            #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+            tls_built_in_root_certs: true,
+            #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+            identity: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            http_version: HttpVersionPreference::Auto,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// The host and address to resolve the environment's host to, if `resolve_ip` is set.
+    pub(crate) fn resolve_override(
+        &self,
+        environment: &Environment,
+    ) -> Result<Option<(String, SocketAddr)>, crate::framework::Error> {
+        let Some(address) = self.resolve_ip else {
+            return Ok(None);
+        };
+
+        let url = url::Url::from(environment);
+        let host = url.host_str().ok_or_else(|| {
+            crate::framework::Error::InvalidEnvironment(url.to_string(), "missing host")
+        })?;
+        let port = url.port_or_known_default().unwrap_or(443);
+
+        Ok(Some((host.to_string(), SocketAddr::new(address, port))))
+    }
+}
+
+/// Applies a [`ClientConfig`] to a `reqwest::ClientBuilder` or `reqwest::blocking::ClientBuilder`,
+/// which share these methods but no trait.
+///
+/// `read_timeout` is not applied, as only the async builder supports it.
+macro_rules! configure_builder {
+    ($builder:expr, $config:expr, $resolve:expr) => {{
+        let config: $crate::framework::client::ClientConfig = $config;
+        let mut builder = $builder
+            .default_headers(config.default_headers)
+            .timeout(config.http_timeout);
+
+        if let Some((host, address)) = $resolve {
+            builder = builder.resolve(&host, address);
         }
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        for proxy in config.proxies {
+            builder = builder.proxy(proxy);
+        }
+        #[cfg(any(feature = "default-tls", feature = "rustls-tls"))]
+        {
+            for certificate in config.root_certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+            builder = builder.tls_built_in_root_certs(config.tls_built_in_root_certs);
+            if let Some(identity) = config.identity {
+                builder = builder.identity(identity);
+            }
+        }
+        if let Some(timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        match config.http_version {
+            $crate::framework::client::HttpVersionPreference::Auto => {}
+            $crate::framework::client::HttpVersionPreference::Http1Only => {
+                builder = builder.http1_only();
+            }
+            #[cfg(feature = "http2")]
+            $crate::framework::client::HttpVersionPreference::Http2PriorKnowledge => {
+                builder = builder.http2_prior_knowledge();
+            }
+        }
+        builder
+    }};
+}
+pub(crate) use configure_builder;
+
+/// Turns a received response into the endpoint's result type, or into an `ApiFailure::Error` /
+/// `ApiFailure::Upstream` when the status is not successful. Unknown fields of successful
+/// responses are reported to `strict_mode`, labelled with `endpoint`.
+pub(crate) fn parse_response<T: ApiResponseType>(
+    response: &ResponseParts,
+    strict_mode: &StrictMode,
+    endpoint: &str,
+) -> ApiResult<T> {
+    if response.status.is_success() {
+        strict_mode.parse(endpoint, &response.body)
+    } else {
+        Err(error_from_response(response))
+    }
+}
+
+/// Builds the failure for a response with a non-successful status.
+pub(crate) fn error_from_response(response: &ResponseParts) -> ApiFailure {
+    let content_type = response
+        .headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let raw = Box::new(RawBody::new(content_type, &response.body));
+
+    match serde_json::from_slice::<ApiErrors>(&response.body) {
+        Ok(errors) => ApiFailure::Error(response.status, errors, raw),
+        Err(_) => ApiFailure::Upstream(response.status, raw),
+    }
+}
+
+/// Like [`parse_response`], but keeps the response metadata next to the result, including when
+/// the response is a failure.
+pub(crate) fn parse_response_with_meta<T: ApiResponseType>(
+    response: ResponseParts,
+    elapsed: Duration,
+    strict_mode: &StrictMode,
+    endpoint: &str,
+) -> ResponseWithMeta<ApiResult<T>> {
+    let result = parse_response(&response, strict_mode, endpoint);
+
+    ResponseWithMeta {
+        result,
+        status: response.status,
+        headers: response.headers,
+        elapsed,
+        raw_body: response.body,
     }
 }
@@ -0,0 +1,75 @@
+use crate::framework::client::error_from_response;
+use crate::framework::client::interceptor::ResponseParts;
+use crate::framework::response::ApiFailure;
+use std::time::Duration;
+
+/// How a client retries requests that failed with a retryable error
+/// (see [`ApiFailure::is_retryable`]).
+///
+/// Every attempt is signed again, with a fresh timestamp. Requests to non-idempotent endpoints
+/// (see [`EndpointSpec::is_idempotent`](crate::framework::endpoint::EndpointSpec::is_idempotent)),
+/// such as `CreatePaymentRequest`, are only retried when the connection could not be established,
+/// as DianDianPay may already have processed them otherwise.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry. Each following retry waits twice as long.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Does not retry.
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy retrying up to `max_retries` times with the default backoff.
+    pub fn with_max_retries(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// The delay before retry number `retry` (starting at 0).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    pub(crate) fn should_retry_failure(
+        &self,
+        retry: u32,
+        failure: &ApiFailure,
+        idempotent: bool,
+    ) -> bool {
+        let retryable = if idempotent {
+            failure.is_retryable()
+        } else {
+            matches!(failure, ApiFailure::Invalid(e) if e.is_connect())
+        };
+        retry < self.max_retries && retryable
+    }
+
+    pub(crate) fn should_retry_response(
+        &self,
+        retry: u32,
+        response: &ResponseParts,
+        idempotent: bool,
+    ) -> bool {
+        retry < self.max_retries
+            && idempotent
+            && !response.status.is_success()
+            && error_from_response(response).is_retryable()
+    }
+}
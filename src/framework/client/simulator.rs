@@ -0,0 +1,143 @@
+use crate::endpoints::payment::notification::PaymentNotification;
+use crate::endpoints::payment::schema::{OrderSummary, PaymentStatus};
+use crate::framework::clock::Clock;
+use crate::framework::keys::PrivateKey;
+use crate::framework::response::{ApiFailure, ApiResult};
+use crate::framework::signature::{AlgorithmError, Credentials, SignatureAlgorithm};
+use chrono_tz::Tz;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use url::Url;
+
+/// Builds signed payment notifications and posts them to a merchant's notification handler, to
+/// test it without DianDianPay.
+///
+/// The notifications follow [`PaymentNotification`], whose payload and signing are
+/// [unverified](crate#unverified-wire-details).
+///
+/// The handler should verify the notifications with the public key of `dd_private_key` in place of
+/// DianDianPay's, e.g. through [`PaymentNotification::from_request`].
+pub struct NotificationSimulator {
+    signer: Credentials,
+}
+
+/// A notification ready to be sent, with its signature headers.
+#[derive(Debug, Clone)]
+pub struct SignedNotification {
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl NotificationSimulator {
+    /// Creates a simulator signing for `merchant_id` with a stand-in for DianDianPay's private key,
+    /// using the default algorithm of its type and the default timezone.
+    pub fn new(merchant_id: impl Into<String>, dd_private_key: impl Into<PrivateKey>) -> Self {
+        let dd_private_key = dd_private_key.into();
+        let dd_public_key = dd_private_key.to_public_key();
+
+        NotificationSimulator {
+            signer: Credentials::new(merchant_id, dd_private_key, dd_public_key),
+        }
+    }
+
+    /// Signs with `algorithm`, which must support the private key's type. Use the algorithm the
+    /// handler verifies DianDianPay's signatures with.
+    pub fn with_algorithm(mut self, algorithm: SignatureAlgorithm) -> Result<Self, AlgorithmError> {
+        self.signer = self.signer.with_signing_algorithm(algorithm)?;
+        Ok(self)
+    }
+
+    /// Sends `timezone` in the `timezone` header.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.signer = self.signer.with_timezone(timezone);
+        self
+    }
+
+    /// Uses `clock` for the `timestamp` header, and the `update_at` of transitioned orders.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.signer = self.signer.with_clock(clock);
+        self
+    }
+
+    /// Builds the notification sent when `order` moves to `status` now, see [`Self::apply`].
+    pub fn transition(
+        &self,
+        order: &OrderSummary,
+        status: PaymentStatus,
+    ) -> ApiResult<SignedNotification> {
+        self.sign(&PaymentNotification {
+            order: Self::apply(order, status, self.signer.clock().now()),
+        })
+    }
+
+    /// Returns `order` as it is after moving to `status` at `now`.
+    ///
+    /// The order's `update_at` is set to `now`. Refunds without a `refunded_amount` refund the full
+    /// amount, and failures without a `failure_reason` get a placeholder one.
+    pub fn apply(
+        order: &OrderSummary,
+        status: PaymentStatus,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> OrderSummary {
+        let mut order = order.clone();
+        match status {
+            PaymentStatus::Refunded if order.refunded_amount.is_none() => {
+                order.refunded_amount = Some(order.gross_amount.value.clone());
+            }
+            PaymentStatus::Failed if order.failure_reason.is_none() => {
+                order.failure_reason = Some("simulated failure".to_string());
+            }
+            _ => {}
+        }
+        order.payment_status = status;
+        order.update_at = now;
+        order
+    }
+
+    /// Signs an arbitrary notification.
+    pub fn sign(&self, notification: &PaymentNotification) -> ApiResult<SignedNotification> {
+        let body = serde_json::to_string(notification).map_err(ApiFailure::Serialization)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        for (name, value) in self.signer.headers(&body) {
+            let value = HeaderValue::try_from(value)
+                .expect("signature headers should be valid header values");
+            headers.insert(HeaderName::from_static(name), value);
+        }
+
+        Ok(SignedNotification { headers, body })
+    }
+}
+
+impl SignedNotification {
+    /// Posts the notification to `url`, returning the status the handler answered with.
+    pub async fn send(&self, client: &reqwest::Client, url: Url) -> ApiResult<StatusCode> {
+        let response = client
+            .post(url)
+            .headers(self.headers.clone())
+            .body(self.body.clone())
+            .send()
+            .await?;
+
+        Ok(response.status())
+    }
+
+    /// Like [`Self::send`], with the blocking client.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn send_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: Url,
+    ) -> ApiResult<StatusCode> {
+        let response = client
+            .post(url)
+            .headers(self.headers.clone())
+            .body(self.body.clone())
+            .send()?;
+
+        Ok(response.status())
+    }
+}
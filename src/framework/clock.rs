@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// The source of the current time, used for the `timestamp` of signed requests.
+///
+/// The default, [`SystemClock`], reads the system time. Tests can pin it with a [`FixedClock`].
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl<T: Clock + ?Sized> Clock for Arc<T> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+/// Reads the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to, with millisecond precision.
+///
+/// Share it through an `Arc` to move it while a client uses it, e.g. to simulate clock skew.
+///
+/// Pinning the clock is enough to make the headers of the default RSA PKCS#1 v1.5 algorithms
+/// reproducible, as their signatures are deterministic. RSA-PSS and ECDSA signatures are not.
+///
+/// ```
+/// # use diandianpay_rs::framework::clock::FixedClock;
+/// # use diandianpay_rs::framework::signature::Credentials;
+/// # use std::sync::Arc;
+/// # fn credentials() -> Credentials {
+/// #     let key = rsa::RsaPrivateKey::new(&mut rand::rngs::OsRng, 1024).unwrap();
+/// #     Credentials::new("merchant", key.clone(), key.to_public_key())
+/// # }
+/// let start = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+/// let clock = Arc::new(FixedClock::new(start));
+/// let credentials = credentials().with_clock(clock.clone());
+///
+/// let headers = credentials.headers("{}");
+/// assert_eq!(headers[1], ("timestamp", "1700000000000".to_string()));
+///
+/// clock.advance(chrono::Duration::minutes(10));
+/// ```
+#[derive(Debug)]
+pub struct FixedClock {
+    millis: AtomicI64,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> FixedClock {
+        FixedClock {
+            millis: AtomicI64::new(now.timestamp_millis()),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.millis.store(now.timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Moves the clock by `delta`, which may be negative.
+    pub fn advance(&self, delta: chrono::Duration) {
+        self.millis
+            .fetch_add(delta.num_milliseconds(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        let millis = self.millis.load(Ordering::Relaxed);
+        DateTime::from_timestamp_millis(millis).expect("clock should be in the supported range")
+    }
+}
+
+/// A cloneable handle to a [`Clock`].
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> SharedClock {
+        SharedClock(Arc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        SharedClock::new(SystemClock)
+    }
+}
+
+impl Debug for SharedClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedClock").field(&self.0.now()).finish()
+    }
+}
+
+impl Clock for SharedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0.now()
+    }
+}
@@ -1,28 +1,537 @@
+//! Endpoint definitions.
+//!
+//! Every DianDianPay API call is a type implementing [`EndpointSpec`]. Endpoints this crate does not
+//! support yet can be defined outside of it the same way:
+//!
+//! ```
+//! use diandianpay_rs::framework::endpoint::{EndpointSpec, Method, RequestBody, json_body};
+//! use diandianpay_rs::framework::response::{ApiFailure, ApiResponse};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize)]
+//! struct RefundRequest {
+//!     merchant_id: String,
+//!     id: String,
+//! }
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct RefundResponse {
+//!     refund_id: String,
+//! }
+//!
+//! impl EndpointSpec for RefundRequest {
+//!     type ResponseType = ApiResponse<RefundResponse>;
+//!
+//!     fn method(&self) -> Method {
+//!         Method::POST
+//!     }
+//!
+//!     fn path(&self) -> String {
+//!         "refund".into()
+//!     }
+//!
+//!     fn body(&self) -> Result<Option<RequestBody<'_>>, ApiFailure> {
+//!         json_body(self)
+//!     }
+//! }
+//! ```
+//!
+//! For one-off calls, [`RawEndpoint`] takes the method, path and JSON body at runtime.
+
 use crate::framework::Environment;
-use crate::framework::response::ApiResponseType;
+use crate::framework::response::{ApiFailure, ApiResponse, ApiResponseType};
+use base64::Engine;
+use rsa::sha2::{Digest, Sha256};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::borrow::Cow;
+use std::fmt::Debug;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::pin::Pin;
 use url::Url;
 
 pub use http::Method;
 
-pub(crate) use spec::EndpointSpec;
+pub use spec::EndpointSpec;
 
+/// The body of a request.
+///
+/// The part of the signed content that represents each kind of body:
+///
+/// - `Json`: the JSON text, exactly as sent.
+/// - `Raw`: the bytes, base64-encoded (standard alphabet, padded).
+/// - `MultiPart`: every part as an url-encoded form (`name=value&...`), in order. Text parts sign
+///   their text; binary and file parts sign the SHA-256 digest of their content, base64-encoded.
+///   Files and readers are read once to be signed, without being held in memory.
+///
+/// The `Raw` and `MultiPart` contents are [unverified](crate#unverified-wire-details).
 pub enum RequestBody<'a> {
     Json(String),
     Raw(Vec<u8>),
     MultiPart(&'a dyn MultipartBody),
 }
 
+/// A request body as the clients sign and send it.
+///
+/// Multipart parts are collected with a single [`MultipartBody::parts`] call, and their content is
+/// read once to be signed: files are kept open, and readers are copied to a temporary file, so the
+/// content that is sent is the content that was signed.
+pub(crate) enum OutgoingBody {
+    Json(String),
+    Raw(Vec<u8>),
+    MultiPart(Vec<OutgoingPart>),
+}
+
+/// A multipart part, with the value it contributes to the signed content.
+pub(crate) struct OutgoingPart {
+    pub(crate) name: String,
+    /// The part's text, or the digest of its content.
+    pub(crate) signed: String,
+    pub(crate) content: OutgoingContent,
+    pub(crate) file_name: Option<String>,
+    pub(crate) mime_type: Option<String>,
+}
+
+pub(crate) enum OutgoingContent {
+    Text(String),
+    Bytes(Vec<u8>),
+    #[cfg(not(target_arch = "wasm32"))]
+    File(SignedFile),
+}
+
+impl OutgoingBody {
+    /// Collects the body, reading multipart files and readers to sign them.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub(crate) fn prepare(body: RequestBody<'_>) -> Result<OutgoingBody, ApiFailure> {
+        let multipart = match body {
+            RequestBody::Json(json) => return Ok(OutgoingBody::Json(json)),
+            RequestBody::Raw(bytes) => return Ok(OutgoingBody::Raw(bytes)),
+            RequestBody::MultiPart(multipart) => multipart,
+        };
+
+        let mut parts = Vec::new();
+        for (name, part) in multipart.parts() {
+            let (file, content) = match part {
+                MultipartPart::File(file) => {
+                    let content = match file.source {
+                        PartSource::Bytes(bytes) => OutgoingContent::Bytes(bytes),
+                        PartSource::Path(ref path) => {
+                            let error =
+                                |e| ApiFailure::MultipartFile(path.display().to_string(), e);
+                            let file = std::fs::File::open(path).map_err(error)?;
+                            OutgoingContent::File(SignedFile::read(file).map_err(error)?)
+                        }
+                        PartSource::Reader(mut reader) => {
+                            let error = |e| ApiFailure::MultipartFile(name.clone(), e);
+                            OutgoingContent::File(SignedFile::spool(&mut reader).map_err(error)?)
+                        }
+                        PartSource::AsyncReader(_) => {
+                            return Err(ApiFailure::UnsupportedBody(format!(
+                                "part {name}: async readers are not supported by the blocking client"
+                            )));
+                        }
+                    };
+                    (Some((file.file_name, file.mime_type)), content)
+                }
+                MultipartPart::Text(text) => (None, OutgoingContent::Text(text)),
+                MultipartPart::Bytes(bytes) => (None, OutgoingContent::Bytes(bytes)),
+            };
+            parts.push(OutgoingPart::new(name, content, file));
+        }
+
+        Ok(OutgoingBody::MultiPart(parts))
+    }
+
+    /// Like [`Self::prepare`], reading files and readers asynchronously.
+    ///
+    /// Multipart parts are collected before the returned future, so it does not borrow the body,
+    /// which is not `Sync`.
+    pub(crate) fn prepare_async(
+        body: RequestBody<'_>,
+    ) -> impl Future<Output = Result<OutgoingBody, ApiFailure>> + Send + 'static {
+        let (single, multipart) = match body {
+            RequestBody::Json(json) => (Some(OutgoingBody::Json(json)), Vec::new()),
+            RequestBody::Raw(bytes) => (Some(OutgoingBody::Raw(bytes)), Vec::new()),
+            RequestBody::MultiPart(multipart) => (None, multipart.parts()),
+        };
+
+        async move {
+            if let Some(body) = single {
+                return Ok(body);
+            }
+
+            let mut parts = Vec::new();
+            for (name, part) in multipart {
+                let (file, content) = match part {
+                    MultipartPart::File(file) => {
+                        let content = match file.source {
+                            PartSource::Bytes(bytes) => OutgoingContent::Bytes(bytes),
+                            #[cfg(not(target_arch = "wasm32"))]
+                            PartSource::Path(ref path) => {
+                                let error =
+                                    |e| ApiFailure::MultipartFile(path.display().to_string(), e);
+                                let file = tokio::fs::File::open(path).await.map_err(error)?;
+                                let file = SignedFile::read_async(file).await;
+                                OutgoingContent::File(file.map_err(error)?)
+                            }
+                            #[cfg(not(target_arch = "wasm32"))]
+                            PartSource::AsyncReader(mut reader) => {
+                                let error = |e| ApiFailure::MultipartFile(name.clone(), e);
+                                let file = SignedFile::spool_async(&mut reader).await;
+                                OutgoingContent::File(file.map_err(error)?)
+                            }
+                            // There is no file system access in wasm.
+                            #[cfg(target_arch = "wasm32")]
+                            PartSource::Path(_) => {
+                                return Err(ApiFailure::UnsupportedBody(
+                                    "file paths are not supported in wasm".to_string(),
+                                ));
+                            }
+                            PartSource::Reader(_) => {
+                                return Err(ApiFailure::UnsupportedBody(format!(
+                                    "part {name}: blocking readers are not supported by the async client"
+                                )));
+                            }
+                        };
+                        (Some((file.file_name, file.mime_type)), content)
+                    }
+                    MultipartPart::Text(text) => (None, OutgoingContent::Text(text)),
+                    MultipartPart::Bytes(bytes) => (None, OutgoingContent::Bytes(bytes)),
+                };
+                parts.push(OutgoingPart::new(name, content, file));
+            }
+
+            Ok(OutgoingBody::MultiPart(parts))
+        }
+    }
+
+    /// The name of each multipart part, with the value it contributes to the signed content.
+    pub(crate) fn signed_parts(&self) -> Vec<(String, String)> {
+        match self {
+            OutgoingBody::MultiPart(parts) => parts
+                .iter()
+                .map(|part| (part.name.clone(), part.signed.clone()))
+                .collect(),
+            OutgoingBody::Json(_) | OutgoingBody::Raw(_) => Vec::new(),
+        }
+    }
+
+    /// The part of the signed content that represents this body, see [`RequestBody`].
+    pub(crate) fn signing_content(&self) -> String {
+        match self {
+            OutgoingBody::Json(json) => json.clone(),
+            OutgoingBody::Raw(bytes) => base64::engine::general_purpose::STANDARD.encode(bytes),
+            OutgoingBody::MultiPart(parts) => {
+                let mut form = url::form_urlencoded::Serializer::new(String::new());
+                for part in parts {
+                    form.append_pair(&part.name, &part.signed);
+                }
+                form.finish()
+            }
+        }
+    }
+}
+
+impl OutgoingPart {
+    fn new(
+        name: String,
+        content: OutgoingContent,
+        file: Option<(Option<String>, Option<String>)>,
+    ) -> OutgoingPart {
+        let signed = match &content {
+            OutgoingContent::Text(text) => text.clone(),
+            OutgoingContent::Bytes(bytes) => digest(bytes),
+            #[cfg(not(target_arch = "wasm32"))]
+            OutgoingContent::File(file) => file.digest.clone(),
+        };
+        let (file_name, mime_type) = file.unwrap_or_default();
+
+        OutgoingPart {
+            name,
+            signed,
+            content,
+            file_name,
+            mime_type,
+        }
+    }
+}
+
+/// The SHA-256 digest of a binary part, base64-encoded.
+fn digest(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(Sha256::digest(bytes))
+}
+
+/// A file part read once to be signed, then rewound to be sent.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct SignedFile {
+    file: std::fs::File,
+    length: u64,
+    digest: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SignedFile {
+    /// Signs the content of an open file.
+    #[cfg(feature = "blocking")]
+    fn read(mut file: std::fs::File) -> std::io::Result<SignedFile> {
+        let (digest, length) = hash_copy(&mut file, &mut std::io::sink())?;
+        SignedFile::rewind(file, length, digest)
+    }
+
+    /// Signs the content of a reader, copying it to a temporary file to be sent from.
+    #[cfg(feature = "blocking")]
+    fn spool(reader: &mut (impl Read + ?Sized)) -> std::io::Result<SignedFile> {
+        let mut file = tempfile::tempfile()?;
+        let (digest, length) = hash_copy(reader, &mut file)?;
+        SignedFile::rewind(file, length, digest)
+    }
+
+    /// Like [`Self::read`], reading asynchronously.
+    async fn read_async(mut file: tokio::fs::File) -> std::io::Result<SignedFile> {
+        let (digest, length) = hash_copy_async(&mut file, &mut tokio::io::sink()).await?;
+        SignedFile::rewind(file.into_std().await, length, digest)
+    }
+
+    /// Like [`Self::spool`], reading and writing asynchronously.
+    async fn spool_async(
+        reader: &mut (impl tokio::io::AsyncRead + Unpin + ?Sized),
+    ) -> std::io::Result<SignedFile> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+        let (digest, length) = hash_copy_async(reader, &mut file).await?;
+        file.flush().await?;
+        SignedFile::rewind(file.into_std().await, length, digest)
+    }
+
+    fn rewind(mut file: std::fs::File, length: u64, digest: String) -> std::io::Result<SignedFile> {
+        std::io::Seek::rewind(&mut file)?;
+        Ok(SignedFile {
+            file,
+            length,
+            digest,
+        })
+    }
+
+    /// A reader of the signed content and its length. Reading fails if the file no longer holds
+    /// as many bytes as were signed.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn into_reader(self) -> (impl Read + Send + 'static, u64) {
+        (ExactLength::new(self.file, self.length), self.length)
+    }
+
+    /// Like [`Self::into_reader`], reading asynchronously.
+    pub(crate) fn into_async_reader(self) -> (impl tokio::io::AsyncRead + Send + 'static, u64) {
+        let file = tokio::fs::File::from_std(self.file);
+        (ExactLength::new(file, self.length), self.length)
+    }
+}
+
+/// Copies `reader` into `sink`, returning the SHA-256 digest of the content, base64-encoded, and
+/// its length.
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+fn hash_copy(
+    reader: &mut (impl Read + ?Sized),
+    sink: &mut impl std::io::Write,
+) -> std::io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let mut length = 0;
+    let mut buffer = [0; 8192];
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            read => {
+                hasher.update(&buffer[..read]);
+                sink.write_all(&buffer[..read])?;
+                length += read as u64;
+            }
+        }
+    }
+    sink.flush()?;
+
+    Ok((
+        base64::engine::general_purpose::STANDARD.encode(hasher.finalize()),
+        length,
+    ))
+}
+
+/// Like [`hash_copy`], reading and writing asynchronously.
+#[cfg(not(target_arch = "wasm32"))]
+async fn hash_copy_async(
+    reader: &mut (impl tokio::io::AsyncRead + Unpin + ?Sized),
+    sink: &mut (impl tokio::io::AsyncWrite + Unpin),
+) -> std::io::Result<(String, u64)> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut hasher = Sha256::new();
+    let mut length = 0;
+    let mut buffer = [0; 8192];
+    loop {
+        match reader.read(&mut buffer).await? {
+            0 => break,
+            read => {
+                hasher.update(&buffer[..read]);
+                sink.write_all(&buffer[..read]).await?;
+                length += read as u64;
+            }
+        }
+    }
+    sink.flush().await?;
+
+    Ok((
+        base64::engine::general_purpose::STANDARD.encode(hasher.finalize()),
+        length,
+    ))
+}
+
+/// Reads exactly `remaining` more bytes from a signed file, failing if it holds more or fewer, e.g.
+/// because it was changed after being signed.
+#[cfg(not(target_arch = "wasm32"))]
+struct ExactLength<R> {
+    inner: R,
+    remaining: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<R> ExactLength<R> {
+    fn new(inner: R, length: u64) -> ExactLength<R> {
+        ExactLength {
+            inner,
+            remaining: length,
+        }
+    }
+
+    /// Accounts for `read` bytes read into a non-empty buffer.
+    fn consume(&mut self, read: usize) -> std::io::Result<()> {
+        let read = read as u64;
+        if read > self.remaining || (read == 0 && self.remaining > 0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the file changed after it was signed",
+            ));
+        }
+        self.remaining -= read;
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+impl<R: Read> Read for ExactLength<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let read = self.inner.read(buf)?;
+        self.consume(read)?;
+        Ok(read)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for ExactLength<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        if buf.remaining() == 0 {
+            return std::task::Poll::Ready(Ok(()));
+        }
+        let filled = buf.filled().len();
+        std::task::ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        std::task::Poll::Ready(self.consume(buf.filled().len() - filled))
+    }
+}
+
 pub enum MultipartPart {
     Text(String),
     Bytes(Vec<u8>),
+    /// A file-like part with an optional file name and MIME type, streamed when the request is
+    /// sent.
+    File(FilePart),
+}
+
+/// Where the content of a [`FilePart`] comes from.
+pub enum PartSource {
+    /// In-memory content.
+    Bytes(Vec<u8>),
+    /// A file, opened once by the client to be signed, then streamed when the request is sent.
+    /// Sending fails if its length changed in between.
+    Path(PathBuf),
+    /// A blocking reader, copied to a temporary file while it is signed, then streamed from it.
+    /// Only supported by the blocking client.
+    Reader(Box<dyn Read + Send>),
+    /// An async reader, copied to a temporary file while it is signed, then streamed from it.
+    /// Only supported by the async client.
+    #[cfg(not(target_arch = "wasm32"))]
+    AsyncReader(Pin<Box<dyn tokio::io::AsyncRead + Send>>),
+}
+
+/// A file-like multipart part.
+///
+/// The content is read once to be signed, then streamed, see [`PartSource`]. As
+/// [`MultipartBody::parts`] is called once per attempt, implementations should open a new reader on
+/// each call.
+pub struct FilePart {
+    pub source: PartSource,
+    pub file_name: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+impl FilePart {
+    pub fn new(source: PartSource) -> FilePart {
+        FilePart {
+            source,
+            file_name: None,
+            mime_type: None,
+        }
+    }
+
+    /// A part streamed from the file at `path`, named after the file.
+    pub fn path(path: impl Into<PathBuf>) -> FilePart {
+        let path = path.into();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+
+        FilePart {
+            file_name,
+            ..FilePart::new(PartSource::Path(path))
+        }
+    }
+
+    pub fn bytes(bytes: impl Into<Vec<u8>>) -> FilePart {
+        FilePart::new(PartSource::Bytes(bytes.into()))
+    }
+
+    pub fn reader(reader: impl Read + Send + 'static) -> FilePart {
+        FilePart::new(PartSource::Reader(Box::new(reader)))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn async_reader(reader: impl tokio::io::AsyncRead + Send + 'static) -> FilePart {
+        FilePart::new(PartSource::AsyncReader(Box::pin(reader)))
+    }
+
+    pub fn file_name(mut self, file_name: impl Into<String>) -> FilePart {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    /// Sets the MIME type, e.g. `application/pdf`.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> FilePart {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
 }
 
 /// Helper trait for endpoints that require a multipart body.
 ///
 /// Mainly exists to allow for client-agnostic multipart body implementations, until reqwest has a
-/// conversion between blocking::multipart::Form/Part and async_impl::multipart::Form/Part.
+/// conversion between blocking::multipart::Form/Part and async_impl::multipart::Form/Part. Files
+/// are described by a [`FilePart`], which each client turns into a streamed part of its own kind.
 pub trait MultipartBody {
     /// Returns a list of parts to be included in a multipart request.
     /// Each part is a tuple of the part name and the part data.
@@ -37,7 +546,7 @@ pub mod spec {
     use super::*;
 
     /// Represents a specification for an API call that can be built into an HTTP request and sent.
-    /// New endpoints should implement this trait.
+    /// New endpoints should implement this trait, both inside and outside of this crate.
     ///
     /// If the request succeeds, the call will resolve to a `ResultType`.
     pub trait EndpointSpec {
@@ -62,17 +571,26 @@ pub mod spec {
         ///
         /// Implementors should inline this.
         #[inline]
-        fn body(&self) -> Option<RequestBody> {
-            None
+        fn body(&self) -> Result<Option<RequestBody<'_>>, ApiFailure> {
+            Ok(None)
+        }
+
+        /// Whether sending the request twice has the same effect as sending it once. Failed
+        /// non-idempotent requests are only retried when they could not reach DianDianPay.
+        ///
+        /// Defaults to whether the method is idempotent; read-only `POST` endpoints should
+        /// override this.
+        fn is_idempotent(&self) -> bool {
+            self.method().is_idempotent()
         }
 
         /// Builds and returns a formatted full URL, including query, for the endpoint.
         ///
         /// Implementors should generally not override this.
-        fn url(&self, environment: &Environment) -> Url {
-            let mut url = Url::from(environment).join(&self.path()).unwrap();
+        fn url(&self, environment: &Environment) -> Result<Url, ApiFailure> {
+            let mut url = Url::from(environment).join(&self.path())?;
             url.set_query(self.query().as_deref());
-            url
+            Ok(url)
         }
 
         //noinspection RsConstantConditionIf
@@ -80,7 +598,7 @@ pub mod spec {
         ///
         /// Implementors generally do not need to override this.
         fn content_type(&self) -> Option<Cow<'static, str>> {
-            match Self::body(self) {
+            match Self::body(self).ok().flatten() {
                 Some(RequestBody::Json(_)) => Some(Cow::Borrowed("application/json")),
                 Some(RequestBody::Raw(_)) => Some(Cow::Borrowed("application/octet-stream")),
                 Some(RequestBody::MultiPart(_)) => Some(Cow::Borrowed("multipart/form-data")),
@@ -97,6 +615,114 @@ impl<T: ApiResponseType, U: EndpointSpec> Endpoint<T> for U {}
 /// If the request succeeds, the call will resolve to a `ResultType`.
 pub trait Endpoint<ResultType: ApiResponseType>: EndpointSpec {}
 
+/// An endpoint described at runtime, for DianDianPay APIs without a dedicated type.
+///
+/// The response is parsed as an [`ApiResponse<T>`], `T` defaulting to a raw JSON value.
+///
+/// ```
+/// use diandianpay_rs::framework::endpoint::RawEndpoint;
+///
+/// let endpoint: RawEndpoint = RawEndpoint::post(
+///     "refund",
+///     serde_json::json!({ "merchant_id": "M123", "id": "P456" }),
+/// );
+///
+/// let endpoint: RawEndpoint = RawEndpoint::get("refund").with_query(&[("id", "P456")])?;
+/// assert_eq!(endpoint.query.as_deref(), Some("id=P456"));
+///
+/// // Nested values cannot be url-encoded.
+/// let nested = serde_json::json!({ "order": { "id": "P456" } });
+/// assert!(RawEndpoint::<serde_json::Value>::get("refund").with_query(&nested).is_err());
+/// # Ok::<(), diandianpay_rs::framework::response::ApiFailure>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RawEndpoint<T = serde_json::Value> {
+    /// The HTTP method.
+    pub method: Method,
+    /// The path, relative to the environment's base URL.
+    pub path: String,
+    /// The url-encoded query string, if any.
+    pub query: Option<String>,
+    /// The JSON body, if any.
+    pub body: Option<serde_json::Value>,
+    /// Whether the request can safely be retried. Defaults to whether the method is idempotent.
+    pub idempotent: bool,
+    response: PhantomData<fn() -> T>,
+}
+
+impl<T> RawEndpoint<T> {
+    pub fn new(method: Method, path: impl Into<String>) -> RawEndpoint<T> {
+        RawEndpoint {
+            idempotent: method.is_idempotent(),
+            method,
+            path: path.into(),
+            query: None,
+            body: None,
+            response: PhantomData,
+        }
+    }
+
+    /// A `GET` request without a body.
+    pub fn get(path: impl Into<String>) -> RawEndpoint<T> {
+        RawEndpoint::new(Method::GET, path)
+    }
+
+    /// A `POST` request with a JSON body.
+    pub fn post(path: impl Into<String>, body: serde_json::Value) -> RawEndpoint<T> {
+        RawEndpoint::new(Method::POST, path).with_body(body)
+    }
+
+    pub fn with_body(mut self, body: serde_json::Value) -> RawEndpoint<T> {
+        self.body = Some(body);
+        self
+    }
+
+    /// Sets the url-encoded query string, failing if `query` cannot be serialized, e.g. because
+    /// it has nested values.
+    pub fn with_query<Q: Serialize>(mut self, query: &Q) -> Result<RawEndpoint<T>, ApiFailure> {
+        self.query =
+            Some(serde_urlencoded::to_string(query).map_err(ApiFailure::QuerySerialization)?);
+        Ok(self)
+    }
+}
+
+impl<T: DeserializeOwned + Debug> EndpointSpec for RawEndpoint<T> {
+    type ResponseType = ApiResponse<T>;
+
+    fn method(&self) -> Method {
+        self.method.clone()
+    }
+
+    fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    #[inline]
+    fn query(&self) -> Option<String> {
+        self.query.clone()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.idempotent
+    }
+
+    #[inline]
+    fn body(&self) -> Result<Option<RequestBody<'_>>, ApiFailure> {
+        match &self.body {
+            Some(body) => json_body(body),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A utility function for serializing a request into a JSON body.
+#[inline]
+pub fn json_body<B: Serialize>(body: &B) -> Result<Option<RequestBody<'static>>, ApiFailure> {
+    serde_json::to_string(body)
+        .map(|json| Some(RequestBody::Json(json)))
+        .map_err(ApiFailure::Serialization)
+}
+
 /// A utility function for serializing parameters into a URL query string.
 #[inline]
 pub fn serialize_query<Q: Serialize>(q: &Q) -> Option<String> {
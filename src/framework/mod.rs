@@ -1,33 +1,121 @@
 pub mod client;
+pub mod clock;
 pub mod endpoint;
+pub mod keys;
 pub mod response;
 pub mod serde;
+pub mod settings;
 pub mod signature;
 
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     /// An error via the `reqwest` crate
-    #[error("Reqwest returned an error when connecting to the Mailgun API: {0}")]
+    #[error("Reqwest returned an error when connecting to the DianDianPay API: {0}")]
     ReqwestError(#[from] reqwest::Error),
+
+    /// A URL that could not be parsed
+    #[error("invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// A base URL that cannot be used as a DianDianPay environment
+    #[error("invalid environment URL {0}: {1}")]
+    InvalidEnvironment(String, &'static str),
+
+    /// An environment name that is neither known nor a URL
+    #[error("unknown environment {0:?}, expected `production`, `test` or a base URL")]
+    UnknownEnvironment(String),
 }
 
-#[derive(Debug)]
+const PRODUCTION_URL: &str = "https://api.diandianpay.com/api/v1/";
+const TEST_URL: &str = "https://test-api.diandianpay.com/api/v1/";
+
+/// The DianDianPay environment to talk to.
+///
+/// Parses from and serializes to `production`, `test` or a base URL, so it can be read from
+/// configuration files and environment variables.
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde_with::SerializeDisplay, serde_with::DeserializeFromStr,
+)]
 pub enum Environment {
     Production,
     Test,
-    Custom(String),
+    /// A custom base URL, built with [`Environment::custom`].
+    Custom(CustomUrl),
+}
+
+/// A validated base URL for [`Environment::Custom`]: an absolute `http`/`https` URL with a host
+/// and a path ending with `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomUrl(url::Url);
+
+impl CustomUrl {
+    pub fn as_url(&self) -> &url::Url {
+        &self.0
+    }
+}
+
+impl Environment {
+    /// Builds a custom environment from a base URL such as `https://proxy.example/api/v1/`.
+    ///
+    /// The URL must be an absolute `http`/`https` URL with a host. A trailing slash is added to
+    /// the path if missing, so endpoint paths are joined below it.
+    pub fn custom(base_url: &str) -> Result<Environment, Error> {
+        let mut url = url::Url::parse(base_url)?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::InvalidEnvironment(
+                base_url.to_string(),
+                "scheme must be http or https",
+            ));
+        }
+        if url.host_str().is_none() {
+            return Err(Error::InvalidEnvironment(
+                base_url.to_string(),
+                "missing host",
+            ));
+        }
+        if !url.path().ends_with('/') {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+
+        Ok(Environment::Custom(CustomUrl(url)))
+    }
 }
 
 impl From<&Environment> for url::Url {
     fn from(env: &Environment) -> Self {
         match env {
-            Environment::Production => {
-                url::Url::parse("https://api.diandianpay.com/api/v1/").unwrap()
-            }
-            Environment::Test => {
-                url::Url::parse("https://test-api.diandianpay.com/api/v1/").unwrap()
-            }
-            Environment::Custom(url) => url::Url::parse(url.as_str()).unwrap(),
+            // The built-in URLs are constants and always parse.
+            Environment::Production => url::Url::parse(PRODUCTION_URL).expect("valid URL"),
+            Environment::Test => url::Url::parse(TEST_URL).expect("valid URL"),
+            Environment::Custom(url) => url.0.clone(),
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "production" | "prod" | "live" => Ok(Environment::Production),
+            "test" | "sandbox" => Ok(Environment::Test),
+            _ if s.contains("://") => Environment::custom(s.trim()),
+            _ => Err(Error::UnknownEnvironment(s.to_string())),
+        }
+    }
+}
+
+impl Display for Environment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Environment::Production => f.write_str("production"),
+            Environment::Test => f.write_str("test"),
+            Environment::Custom(url) => f.write_str(url.0.as_str()),
         }
     }
 }
@@ -1,7 +1,8 @@
-use crate::framework::response::ApiResultMessage;
+use crate::framework::response::{ApiResultMessage, DianDianPayErrorCode};
 use crate::framework::signature::VerifySignatureError;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug, Deserialize)]
 pub struct EmptyStruct {}
@@ -16,10 +17,64 @@ pub struct ApiErrorData {
     pub result: ApiResultMessage,
 }
 
+/// The beginning of an error response body, kept for diagnostics.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RawBody {
+    /// The `Content-Type` of the response, if it had one.
+    pub content_type: Option<String>,
+
+    /// The body as text, truncated to [`RawBody::MAX_LEN`] bytes.
+    pub text: String,
+
+    /// Whether `text` was truncated.
+    pub truncated: bool,
+}
+
+impl RawBody {
+    /// The maximum number of bytes of the body that are kept.
+    pub const MAX_LEN: usize = 4096;
+
+    pub(crate) fn new(content_type: Option<&str>, body: &[u8]) -> RawBody {
+        let mut text = String::from_utf8_lossy(body).into_owned();
+        let truncated = text.len() > Self::MAX_LEN;
+        if truncated {
+            let mut len = Self::MAX_LEN;
+            while !text.is_char_boundary(len) {
+                len -= 1;
+            }
+            text.truncate(len);
+        }
+
+        RawBody {
+            content_type: content_type.map(str::to_string),
+            text,
+            truncated,
+        }
+    }
+}
+
+impl Display for RawBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(content_type) = &self.content_type {
+            write!(f, "({content_type}) ")?;
+        }
+        f.write_str(&self.text)?;
+        if self.truncated {
+            f.write_str("...")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ApiFailure {
+    /// A non-2xx response carrying DianDianPay's structured error body.
     #[error("HTTP {0} - {1:?}")]
-    Error(reqwest::StatusCode, ApiErrors),
+    Error(StatusCode, ApiErrors, Box<RawBody>),
+
+    /// A non-2xx response whose body is not a DianDianPay error, e.g. an HTML page from a gateway.
+    #[error("HTTP {0} - unexpected error body: {1}")]
+    Upstream(StatusCode, Box<RawBody>),
 
     #[error(transparent)]
     Invalid(#[from] reqwest::Error),
@@ -27,22 +82,138 @@ pub enum ApiFailure {
     #[error("Decoding Error - {0}")]
     Decoding(#[from] serde_json::Error),
 
+    #[error("Serialization Error - {0}")]
+    Serialization(#[source] serde_json::Error),
+
+    #[error("Query Serialization Error - {0}")]
+    QuerySerialization(#[source] serde_urlencoded::ser::Error),
+
+    #[error("invalid request URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    #[error("could not open multipart file {0}: {1}")]
+    MultipartFile(String, #[source] std::io::Error),
+
+    #[error("unsupported request body: {0}")]
+    UnsupportedBody(String),
+
     #[error(transparent)]
     InvalidSignature(#[from] VerifySignatureError),
 
     #[error("missing required header: {0}")]
     MissingHeader(String),
+
+    #[error("interceptor error: {0}")]
+    Interceptor(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl ApiFailure {
+    /// The HTTP status of the response, for failures that received one.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiFailure::Error(status, _, _) | ApiFailure::Upstream(status, _) => Some(*status),
+            ApiFailure::Invalid(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// The result message of DianDianPay's structured error body, if any.
+    pub fn result_message(&self) -> Option<&ApiResultMessage> {
+        match self {
+            ApiFailure::Error(_, errors, _) => Some(&errors.data.result),
+            _ => None,
+        }
+    }
+
+    /// The typed DianDianPay error code, if the failure carries a specific one.
+    pub fn error_code(&self) -> Option<DianDianPayErrorCode> {
+        self.result_message()?.code.as_ref()?.error_code()
+    }
+
+    /// Whether the request timed out, either locally or at an upstream gateway.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            ApiFailure::Invalid(e) => e.is_timeout(),
+            _ => matches!(
+                self.status(),
+                Some(StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT)
+            ),
+        }
+    }
+
+    /// Whether sending the same request again may succeed.
+    ///
+    /// Covers timeouts, connection failures, rate limiting and transient server errors. Failures
+    /// with a known, permanent DianDianPay error code are never considered retryable.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_timeout() {
+            return true;
+        }
+        if self.error_code().is_some_and(|code| code.is_permanent()) {
+            return false;
+        }
+        match self {
+            ApiFailure::Invalid(e) => e.is_connect(),
+            _ => matches!(
+                self.status(),
+                Some(
+                    StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::INTERNAL_SERVER_ERROR
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                )
+            ),
+        }
+    }
+
+    /// Whether DianDianPay rejected the request's credentials or signature, or a received
+    /// signature could not be verified.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(self, ApiFailure::InvalidSignature(_))
+            || matches!(
+                self.status(),
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            )
+            || self
+                .error_code()
+                .is_some_and(|code| code == DianDianPayErrorCode::InvalidSignature)
+    }
+
+    /// Whether the failure should be shown to the customer, e.g. a declined card.
+    pub fn is_user_facing(&self) -> bool {
+        self.error_code().is_some_and(|code| code.is_user_facing())
+    }
+
+    /// Whether the failure points at the merchant's credentials or configuration rather than at
+    /// the customer or the network.
+    pub fn is_merchant_config_error(&self) -> bool {
+        self.is_auth_error()
+            || self
+                .error_code()
+                .is_some_and(|code| code.is_merchant_config())
+    }
 }
 
 impl PartialEq for ApiFailure {
     fn eq(&self, other: &ApiFailure) -> bool {
         use ApiFailure::*;
         match (self, other) {
-            (Error(status1, e1), Error(status2, e2)) => status1 == status2 && e1 == e2,
+            (Error(status1, e1, raw1), Error(status2, e2, raw2)) => {
+                status1 == status2 && e1 == e2 && raw1 == raw2
+            }
+            (Upstream(status1, raw1), Upstream(status2, raw2)) => {
+                status1 == status2 && raw1 == raw2
+            }
             (Invalid(e1), Invalid(e2)) => e1.to_string() == e2.to_string(),
             (Decoding(e1), Decoding(e2)) => e1.to_string() == e2.to_string(),
+            (Serialization(e1), Serialization(e2)) => e1.to_string() == e2.to_string(),
+            (QuerySerialization(e1), QuerySerialization(e2)) => e1 == e2,
+            (InvalidUrl(e1), InvalidUrl(e2)) => e1 == e2,
+            (MultipartFile(p1, e1), MultipartFile(p2, e2)) => p1 == p2 && e1.kind() == e2.kind(),
+            (UnsupportedBody(m1), UnsupportedBody(m2)) => m1 == m2,
             (InvalidSignature(e1), InvalidSignature(e2)) => e1.to_string() == e2.to_string(),
             (MissingHeader(h1), MissingHeader(h2)) => h1 == h2,
+            (Interceptor(e1), Interceptor(e2)) => e1.to_string() == e2.to_string(),
             _ => false,
         }
     }
@@ -0,0 +1,64 @@
+use crate::framework::response::ApiResultCode;
+
+/// Typed view of the `result_code` values DianDianPay returns for failed requests.
+///
+/// Codes this crate does not know about are kept as `Unknown`.
+///
+/// Each variant matches its own name in `SCREAMING_SNAKE_CASE`, such as `CARD_DECLINED`. These
+/// strings are [unverified](crate#unverified-wire-details).
+#[derive(Debug, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
+#[allow(missing_docs)]
+pub enum DianDianPayErrorCode {
+    CardDeclined,
+    InsufficientFunds,
+    ThreeDsFailed,
+    InvalidSignature,
+    DuplicateOrder,
+    Unknown(String),
+}
+
+impl DianDianPayErrorCode {
+    /// Maps a raw `result_code` to a typed error code.
+    pub fn from_code(code: &str) -> DianDianPayErrorCode {
+        match code.to_ascii_uppercase().as_str() {
+            "CARD_DECLINED" => DianDianPayErrorCode::CardDeclined,
+            "INSUFFICIENT_FUNDS" => DianDianPayErrorCode::InsufficientFunds,
+            "THREE_DS_FAILED" => DianDianPayErrorCode::ThreeDsFailed,
+            "INVALID_SIGNATURE" => DianDianPayErrorCode::InvalidSignature,
+            "DUPLICATE_ORDER" => DianDianPayErrorCode::DuplicateOrder,
+            _ => DianDianPayErrorCode::Unknown(code.to_string()),
+        }
+    }
+
+    /// Whether the error is caused by the customer's payment method, and should be shown to the
+    /// customer so they can try again with another one.
+    pub fn is_user_facing(&self) -> bool {
+        matches!(
+            self,
+            DianDianPayErrorCode::CardDeclined
+                | DianDianPayErrorCode::InsufficientFunds
+                | DianDianPayErrorCode::ThreeDsFailed
+        )
+    }
+
+    /// Whether sending the same request again cannot succeed. Unknown codes may be transient, e.g.
+    /// a busy code, so they are not permanent.
+    pub fn is_permanent(&self) -> bool {
+        !matches!(self, DianDianPayErrorCode::Unknown(_))
+    }
+
+    /// Whether the error points at a problem with the merchant's credentials or configuration.
+    pub fn is_merchant_config(&self) -> bool {
+        matches!(self, DianDianPayErrorCode::InvalidSignature)
+    }
+}
+
+impl ApiResultCode {
+    /// The typed error code, for result codes that are not one of the generic statuses.
+    pub fn error_code(&self) -> Option<DianDianPayErrorCode> {
+        match self {
+            ApiResultCode::Custom(code) => Some(DianDianPayErrorCode::from_code(code)),
+            _ => None,
+        }
+    }
+}
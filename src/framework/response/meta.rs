@@ -0,0 +1,52 @@
+use http::{HeaderMap, StatusCode};
+use std::borrow::Cow;
+use std::time::Duration;
+
+/// Header names DianDianPay may use to identify a request, in order of preference.
+const REQUEST_ID_HEADERS: [&str; 3] = ["dd-request-id", "request-id", "x-request-id"];
+
+/// A parsed result together with the metadata of the HTTP response it was parsed from.
+#[derive(Debug, Clone)]
+pub struct ResponseWithMeta<T> {
+    /// The parsed result, as returned by `request`.
+    pub result: T,
+
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+
+    /// The response headers, including DianDianPay's signature headers.
+    pub headers: HeaderMap,
+
+    /// Time from sending the request until the full body was received.
+    pub elapsed: Duration,
+
+    /// The unparsed response body.
+    pub raw_body: bytes::Bytes,
+}
+
+impl<T> ResponseWithMeta<T> {
+    /// The DianDianPay request id of this response, if the response carries one.
+    pub fn request_id(&self) -> Option<&str> {
+        REQUEST_ID_HEADERS.iter().find_map(|name| self.header(name))
+    }
+
+    /// The `signature` header DianDianPay signed this response with, if any.
+    pub fn signature(&self) -> Option<&str> {
+        self.header("signature")
+    }
+
+    /// Returns the value of a response header, if it is present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// The raw body as text, with invalid UTF-8 sequences replaced.
+    pub fn raw_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.raw_body)
+    }
+
+    /// Discards the metadata and returns the parsed result.
+    pub fn into_result(self) -> T {
+        self.result
+    }
+}
@@ -1,9 +1,15 @@
 mod api_fail;
+mod error_code;
+mod meta;
+mod strict;
 
 pub use api_fail::*;
+pub use error_code::*;
+pub use meta::*;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+pub use strict::{StrictMode, UnknownFields};
 
 #[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone, strum_macros::AsRefStr)]
 #[serde(rename_all = "UPPERCASE")]
@@ -25,6 +31,8 @@ pub enum ApiResultStatus {
     #[default]
     #[serde(rename = "F")]
     Failed,
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 pub type ApiResult<ResultType> = Result<ResultType, ApiFailure>;
@@ -53,10 +61,65 @@ pub struct ApiResultMessage {
     pub message: Option<String>,
 }
 
-pub trait JsonResult: DeserializeOwned + Debug {}
+/// Marks a type as parsed from a JSON response body.
+pub trait JsonResult: DeserializeOwned + Debug {
+    /// Parses the body, passing the JSON path of every ignored field to `unknown`.
+    fn from_json_reporting(
+        bytes: &[u8],
+        unknown: &mut dyn FnMut(String),
+    ) -> Result<Self, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let result = serde_ignored::deserialize(&mut deserializer, |path| {
+            unknown(strict::json_path("", &path))
+        })?;
+        deserializer.end()?;
+        Ok(result)
+    }
+}
+
+impl<T: DeserializeOwned + Debug> JsonResult for ApiResponse<T> {
+    fn from_json_reporting(
+        bytes: &[u8],
+        unknown: &mut dyn FnMut(String),
+    ) -> Result<Self, serde_json::Error> {
+        let response = serde_json::from_slice(bytes)?;
+
+        // `ApiData` flattens `T`, which hides its ignored fields from `serde_ignored`. Walk the
+        // envelope by hand and check `T` and the result message on their own instead.
+        let serde_json::Value::Object(mut root) = serde_json::from_slice(bytes)? else {
+            return Ok(response);
+        };
+        let data = root.remove("data");
+        root.keys().for_each(|key| unknown(key.clone()));
+
+        if let Some(serde_json::Value::Object(mut data)) = data {
+            if let Some(result) = data.remove("result") {
+                serde_ignored::deserialize::<_, _, ApiResultMessage>(result, |path| {
+                    unknown(strict::json_path("data.result", &path))
+                })?;
+            }
+            serde_ignored::deserialize::<_, _, T>(serde_json::Value::Object(data), |path| {
+                unknown(strict::json_path("data", &path))
+            })?;
+        }
+
+        Ok(response)
+    }
+}
 
 pub trait ApiResponseType: Sized {
     fn from_response(bytes: &bytes::Bytes) -> Result<Self, ApiFailure>;
+
+    /// Like [`Self::from_response`], also passing the JSON path of every field the response type
+    /// ignored to `unknown`. Used by [`StrictMode`]; types that are not parsed from JSON report
+    /// nothing.
+    fn from_response_reporting(
+        bytes: &bytes::Bytes,
+        unknown: &mut dyn FnMut(String),
+    ) -> Result<Self, ApiFailure> {
+        let _ = unknown;
+        Self::from_response(bytes)
+    }
 }
 
 impl<T> ApiResponseType for T
@@ -66,6 +129,13 @@ where
     fn from_response(bytes: &bytes::Bytes) -> Result<Self, ApiFailure> {
         serde_json::from_slice(bytes).map_err(ApiFailure::Decoding)
     }
+
+    fn from_response_reporting(
+        bytes: &bytes::Bytes,
+        unknown: &mut dyn FnMut(String),
+    ) -> Result<Self, ApiFailure> {
+        T::from_json_reporting(bytes, unknown).map_err(ApiFailure::Decoding)
+    }
 }
 
 impl ApiResponseType for String {
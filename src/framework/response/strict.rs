@@ -0,0 +1,77 @@
+use crate::framework::response::{ApiFailure, ApiResponseType};
+use std::sync::Arc;
+
+/// Response fields that were present in a response but ignored when parsing it.
+#[derive(Debug)]
+pub struct UnknownFields<'a> {
+    /// The path of the endpoint that returned the response.
+    pub endpoint: &'a str,
+    /// The JSON paths of the ignored fields, e.g. `data.order.new_field`.
+    pub fields: &'a [String],
+}
+
+type ReportFn = dyn Fn(&UnknownFields<'_>) + Send + Sync;
+
+/// Opt-in diagnostic mode reporting response fields this crate does not know about.
+///
+/// Unknown fields never fail a request; they are passed to the reporting callback, which is only
+/// called for responses that have any. Parsing is slower in this mode, as responses are walked a
+/// second time.
+#[derive(Clone, Default)]
+pub struct StrictMode {
+    report: Option<Arc<ReportFn>>,
+}
+
+impl StrictMode {
+    /// Does not look for unknown fields. This is the default.
+    pub fn disabled() -> StrictMode {
+        StrictMode::default()
+    }
+
+    /// Calls `report` for every response with unknown fields.
+    pub fn report<F>(report: F) -> StrictMode
+    where
+        F: Fn(&UnknownFields<'_>) + Send + Sync + 'static,
+    {
+        StrictMode {
+            report: Some(Arc::new(report)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.report.is_some()
+    }
+
+    pub(crate) fn parse<T: ApiResponseType>(
+        &self,
+        endpoint: &str,
+        bytes: &bytes::Bytes,
+    ) -> Result<T, ApiFailure> {
+        let Some(report) = &self.report else {
+            return T::from_response(bytes);
+        };
+
+        let mut fields = Vec::new();
+        let result = T::from_response_reporting(bytes, &mut |path| fields.push(path))?;
+        if !fields.is_empty() {
+            report(&UnknownFields {
+                endpoint,
+                fields: &fields,
+            });
+        }
+        Ok(result)
+    }
+}
+
+/// Turns a `serde_ignored` path into a dotted JSON path, dropping the markers for `Option`s and
+/// newtypes.
+pub(crate) fn json_path(prefix: &str, path: &serde_ignored::Path<'_>) -> String {
+    let path = path.to_string();
+    let segments = path.split('.').filter(|s| !s.is_empty() && *s != "?");
+
+    std::iter::once(prefix)
+        .filter(|p| !p.is_empty())
+        .chain(segments)
+        .collect::<Vec<_>>()
+        .join(".")
+}
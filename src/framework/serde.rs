@@ -59,3 +59,58 @@ pub mod rsa {
         }
     }
 }
+
+/// Serializes a [`PrivateKey`](crate::framework::keys::PrivateKey) or
+/// [`PublicKey`](crate::framework::keys::PublicKey) as base64 PKCS#8 or SPKI DER, like [`rsa`]
+/// does for RSA keys.
+pub mod key {
+    pub mod private {
+        use crate::framework::keys::{self, KeyFormat, PrivateKey};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S>(key: &PrivateKey, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let encoded = key
+                .to_bytes(KeyFormat::Base64Der)
+                .map_err(serde::ser::Error::custom)?;
+
+            String::from_utf8_lossy(&encoded).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<PrivateKey, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let encoded: &str = Deserialize::deserialize(deserializer)?;
+
+            keys::any_private_key_from_bytes(encoded.as_bytes()).map_err(serde::de::Error::custom)
+        }
+    }
+
+    pub mod public {
+        use crate::framework::keys::{self, KeyFormat, PublicKey};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S>(key: &PublicKey, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let encoded = key
+                .to_bytes(KeyFormat::Base64Der)
+                .map_err(serde::ser::Error::custom)?;
+
+            String::from_utf8_lossy(&encoded).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<PublicKey, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let encoded: &str = Deserialize::deserialize(deserializer)?;
+
+            keys::any_public_key_from_bytes(encoded.as_bytes()).map_err(serde::de::Error::custom)
+        }
+    }
+}
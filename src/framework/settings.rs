@@ -0,0 +1,280 @@
+use crate::framework::Environment;
+use crate::framework::client::ClientConfig;
+use crate::framework::keys::{self, KeyError};
+use crate::framework::signature::{
+    AlgorithmError, Credentials, SignatureAlgorithm, VerificationKey,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The prefix of the environment variables read by [`ClientSettings::from_env`].
+pub const ENV_PREFIX: &str = "DIANDIANPAY_";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("could not read settings file {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+
+    #[error("invalid JSON settings: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "toml")]
+    #[error("invalid TOML settings: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("unsupported settings file extension: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("missing environment variable {0}")]
+    MissingVar(String),
+
+    #[error("invalid value for environment variable {0}: {1}")]
+    InvalidVar(String, String),
+
+    #[error("no credentials configured")]
+    MissingCredentials,
+
+    #[error(transparent)]
+    Key(#[from] KeyError),
+
+    #[error(transparent)]
+    Algorithm(#[from] AlgorithmError),
+}
+
+/// Deployment settings for a client, loadable from environment variables, JSON or TOML.
+///
+/// A TOML file looks like:
+///
+/// ```toml
+/// environment = "test"
+/// http_timeout_secs = 30
+///
+/// [credentials]
+/// merchant_id = "M123"
+/// private_key_path = "/etc/diandianpay/private.pem"
+/// dd_public_key_path = "/etc/diandianpay/dd_public.pem"
+/// timezone = "Asia/Ho_Chi_Minh"
+///
+/// [[credentials.dd_rotation_keys]]
+/// key_id = "2025-01"
+/// path = "/etc/diandianpay/dd_public_next.pem"
+/// algorithm = "RSA-PSS-SHA256"
+/// not_before = "2025-01-01T00:00:00Z"
+/// ```
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientSettings {
+    /// `production`, `test` or a base URL.
+    pub environment: Environment,
+
+    /// The maximum time limit for an API request, in seconds.
+    pub http_timeout_secs: Option<u64>,
+
+    /// A specific IP to use when establishing a connection.
+    pub resolve_ip: Option<IpAddr>,
+
+    pub credentials: Option<CredentialsSettings>,
+}
+
+/// Where to find the credentials of a merchant account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialsSettings {
+    /// Merchant unique identifier
+    pub merchant_id: String,
+
+    /// The merchant's private key, RSA or with the `p256` feature P-256, read with
+    /// [`keys::read_any_private_key`].
+    pub private_key_path: PathBuf,
+
+    /// DianDianPay's public key, RSA or with the `p256` feature P-256, read with
+    /// [`keys::read_any_public_key`].
+    pub dd_public_key_path: PathBuf,
+
+    /// The IANA timezone sent with signed requests, e.g. `Asia/Ho_Chi_Minh`. Defaults to
+    /// `Etc/UTC`.
+    #[serde(default)]
+    pub timezone: Option<Tz>,
+
+    /// The merchant's signature algorithm, e.g. `RSA-PSS-SHA256`. Defaults to the one of the
+    /// private key's type.
+    #[serde(default)]
+    pub algorithm: Option<SignatureAlgorithm>,
+
+    /// The algorithm of DianDianPay's signatures made with `dd_public_key_path`. Defaults to the
+    /// one of the key's type.
+    #[serde(default)]
+    pub dd_algorithm: Option<SignatureAlgorithm>,
+
+    /// Additional DianDianPay public keys accepted during a key rotation. Only read from files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dd_rotation_keys: Vec<VerificationKeySettings>,
+}
+
+/// Where to find an additional DianDianPay public key, see [`Credentials::with_verification_key`].
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationKeySettings {
+    pub key_id: Option<String>,
+
+    /// The public key, in any format accepted by [`keys::any_public_key_from_bytes`].
+    pub path: PathBuf,
+
+    /// The algorithm of signatures made with the key. Defaults to the one of the key's type.
+    pub algorithm: Option<SignatureAlgorithm>,
+
+    pub not_before: Option<DateTime<Utc>>,
+
+    pub not_after: Option<DateTime<Utc>>,
+}
+
+impl ClientSettings {
+    /// Reads settings from `DIANDIANPAY_*` environment variables:
+    ///
+    /// - `DIANDIANPAY_ENVIRONMENT` (required)
+    /// - `DIANDIANPAY_HTTP_TIMEOUT_SECS`
+    /// - `DIANDIANPAY_RESOLVE_IP`
+    /// - `DIANDIANPAY_MERCHANT_ID`, `DIANDIANPAY_PRIVATE_KEY_PATH` and
+    ///   `DIANDIANPAY_DD_PUBLIC_KEY_PATH`, which must be set together
+    /// - `DIANDIANPAY_TIMEZONE`
+    /// - `DIANDIANPAY_SIGNATURE_ALGORITHM`
+    /// - `DIANDIANPAY_DD_SIGNATURE_ALGORITHM`
+    pub fn from_env() -> Result<ClientSettings, SettingsError> {
+        Self::from_vars(|name| std::env::var(format!("{ENV_PREFIX}{name}")).ok())
+    }
+
+    /// Reads settings from variables looked up by `var`, using the names of [`Self::from_env`]
+    /// without the prefix.
+    pub fn from_vars(
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<ClientSettings, SettingsError> {
+        let invalid = |name: &str, e: &dyn std::fmt::Display| {
+            SettingsError::InvalidVar(format!("{ENV_PREFIX}{name}"), e.to_string())
+        };
+        let required = |name: &str| {
+            var(name).ok_or_else(|| SettingsError::MissingVar(format!("{ENV_PREFIX}{name}")))
+        };
+
+        let environment = required("ENVIRONMENT")?
+            .parse()
+            .map_err(|e| invalid("ENVIRONMENT", &e))?;
+
+        let http_timeout_secs = var("HTTP_TIMEOUT_SECS")
+            .map(|v| v.parse().map_err(|e| invalid("HTTP_TIMEOUT_SECS", &e)))
+            .transpose()?;
+
+        let resolve_ip = var("RESOLVE_IP")
+            .map(|v| v.parse().map_err(|e| invalid("RESOLVE_IP", &e)))
+            .transpose()?;
+
+        let credentials = match var("MERCHANT_ID") {
+            Some(merchant_id) => Some(CredentialsSettings {
+                merchant_id,
+                private_key_path: required("PRIVATE_KEY_PATH")?.into(),
+                dd_public_key_path: required("DD_PUBLIC_KEY_PATH")?.into(),
+                timezone: var("TIMEZONE")
+                    .map(|v| v.parse().map_err(|e| invalid("TIMEZONE", &e)))
+                    .transpose()?,
+                algorithm: var("SIGNATURE_ALGORITHM")
+                    .map(|v| v.parse().map_err(|e| invalid("SIGNATURE_ALGORITHM", &e)))
+                    .transpose()?,
+                dd_algorithm: var("DD_SIGNATURE_ALGORITHM")
+                    .map(|v| v.parse().map_err(|e| invalid("DD_SIGNATURE_ALGORITHM", &e)))
+                    .transpose()?,
+                dd_rotation_keys: Vec::new(),
+            }),
+            None => None,
+        };
+
+        Ok(ClientSettings {
+            environment,
+            http_timeout_secs,
+            resolve_ip,
+            credentials,
+        })
+    }
+
+    /// Parses settings from a JSON document.
+    pub fn from_json_str(json: &str) -> Result<ClientSettings, SettingsError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Parses settings from a TOML document.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<ClientSettings, SettingsError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Reads settings from a `.json` or (with the `toml` feature) `.toml` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ClientSettings, SettingsError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| SettingsError::Io(path.display().to_string(), e))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json_str(&content),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml_str(&content),
+            _ => Err(SettingsError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Builds a [`ClientConfig`] from these settings, using defaults for anything not set.
+    pub fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::default();
+        if let Some(secs) = self.http_timeout_secs {
+            config.http_timeout = Duration::from_secs(secs);
+        }
+        config.resolve_ip = self.resolve_ip;
+        config
+    }
+
+    /// Loads the configured credentials, reading the key files.
+    pub fn load_credentials(&self) -> Result<Credentials, SettingsError> {
+        self.credentials
+            .as_ref()
+            .ok_or(SettingsError::MissingCredentials)?
+            .load()
+    }
+}
+
+impl CredentialsSettings {
+    /// Reads the key files and builds the credentials.
+    pub fn load(&self) -> Result<Credentials, SettingsError> {
+        let mut credentials = Credentials::new(
+            self.merchant_id.clone(),
+            keys::read_any_private_key(&self.private_key_path)?,
+            keys::read_any_public_key(&self.dd_public_key_path)?,
+        );
+        if let Some(timezone) = self.timezone {
+            credentials = credentials.with_timezone(timezone);
+        }
+        if let Some(algorithm) = self.algorithm {
+            credentials = credentials.with_signing_algorithm(algorithm)?;
+        }
+        if let Some(algorithm) = self.dd_algorithm {
+            credentials = credentials.with_verification_algorithm(algorithm)?;
+        }
+        for key in &self.dd_rotation_keys {
+            credentials = credentials.with_verification_key(key.load()?)?;
+        }
+
+        Ok(credentials)
+    }
+}
+
+impl VerificationKeySettings {
+    /// Reads the key file.
+    pub fn load(&self) -> Result<VerificationKey, SettingsError> {
+        Ok(VerificationKey {
+            key_id: self.key_id.clone(),
+            key: keys::read_any_public_key(&self.path)?,
+            algorithm: self.algorithm,
+            not_before: self.not_before,
+            not_after: self.not_after,
+        })
+    }
+}
@@ -1,3 +1,25 @@
+//! A client for the DianDianPay payment API.
+//!
+//! # Unverified wire details
+//!
+//! Some details of the wire format are this crate's own choices, not taken from DianDianPay's
+//! documentation. Check them with DianDianPay before relying on them:
+//!
+//! - The `result_code` strings matched by
+//!   [`DianDianPayErrorCode`](framework::response::DianDianPayErrorCode): each variant matches
+//!   only its own name in `SCREAMING_SNAKE_CASE`, such as `CARD_DECLINED`.
+//! - The signed content of [`RequestBody::Raw`](framework::endpoint::RequestBody::Raw) and
+//!   [`RequestBody::MultiPart`](framework::endpoint::RequestBody::MultiPart) bodies. JSON bodies
+//!   are signed as they always have been by this crate.
+//! - The [`ID_PARAM`](endpoints::payment::redirect::ID_PARAM) and
+//!   [`PAYMENT_STATUS_PARAM`](endpoints::payment::redirect::PAYMENT_STATUS_PARAM) query
+//!   parameters of return URLs.
+//! - The `type` values of [`NextAction`](endpoints::payment::schema::NextAction): `REDIRECT`,
+//!   `THREE_DS_CHALLENGE` and `NONE`.
+//! - The payload of [`PaymentNotification`](endpoints::payment::notification::PaymentNotification),
+//!   an object with the order under `order`, and that notifications are signed like API
+//!   responses.
+
 #![forbid(unsafe_code)]
 extern crate alloc;
 extern crate core;